@@ -38,6 +38,15 @@ impl BitSet {
         self.buf.iter().map(|x| x.count_ones()).sum()
     }
 
+    #[allow(dead_code)]
+    /// Index of the lowest set bit
+    pub fn first_one(&self) -> Option<usize> {
+        self.buf
+            .iter()
+            .position(|&x| x != 0)
+            .map(|i| i * 64 + self.buf[i].trailing_zeros() as usize)
+    }
+
     #[allow(dead_code)]
     fn chomp(&mut self) {
         let r = self.size & 63;
@@ -219,6 +228,18 @@ fn test_bitset_set_read() {
     }
 }
 
+#[test]
+fn test_bitset_first_one() {
+    let mut set = BitSet::new(200);
+    assert_eq!(set.first_one(), None);
+    set.set(150, true);
+    assert_eq!(set.first_one(), Some(150));
+    set.set(64, true);
+    assert_eq!(set.first_one(), Some(64));
+    set.set(3, true);
+    assert_eq!(set.first_one(), Some(3));
+}
+
 #[test]
 fn test_bitset_shl() {
     let do_test = |size, shift| {
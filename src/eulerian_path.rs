@@ -0,0 +1,158 @@
+use crate::graph::Graph;
+use cargo_snippet::snippet;
+
+#[snippet("eulerian_path")]
+#[snippet(include = "Graph")]
+#[allow(dead_code)]
+/// Hierholzer's algorithm without recursion. Multiple edges and self-loops are allowed.
+/// Returns (vertices, edge ids) of a path using every edge exactly once,
+/// starting from `start` if given. `vertices.len() == edges.len() + 1`.
+/// The path is a circuit if every vertex has even degree (in-degree == out-degree).
+/// O(V + E)
+pub fn eulerian_path<W>(g: &Graph<W>, start: Option<usize>) -> Option<(Vec<usize>, Vec<usize>)> {
+    let n = g.len();
+    let m = g.edges.len();
+    if n == 0 {
+        return None;
+    }
+
+    // out - in for directed graphs, degree mod 2 for undirected graphs
+    let mut balance = vec![0i64; n];
+    for &(u, v, _) in &g.edges {
+        if g.directed {
+            balance[u] += 1;
+            balance[v] -= 1;
+        } else {
+            balance[u] ^= 1;
+            balance[v] ^= 1;
+        }
+    }
+    let odd = (0..n).filter(|&v| balance[v] != 0).collect::<Vec<_>>();
+
+    let s = if odd.is_empty() {
+        match start {
+            Some(s) if m == 0 || !g.adj(s).is_empty() => s,
+            Some(_) => return None,
+            None => g.edges.first().map(|e| e.0).unwrap_or(0),
+        }
+    } else {
+        let candidates = if g.directed {
+            if odd.len() != 2 || odd.iter().any(|&v| balance[v].abs() != 1) {
+                return None;
+            }
+            vec![*odd.iter().find(|&&v| balance[v] == 1).unwrap()]
+        } else if odd.len() == 2 {
+            odd
+        } else {
+            return None;
+        };
+        match start {
+            Some(s) if candidates.contains(&s) => s,
+            Some(_) => return None,
+            None => candidates[0],
+        }
+    };
+
+    let mut used = vec![false; m];
+    let mut next = vec![0; n];
+    let mut vertices = Vec::with_capacity(m + 1);
+    let mut edges = Vec::with_capacity(m + 1);
+    // vertex, edge id used to reach it
+    let mut stack = vec![(s, !0)];
+    while let Some(&(v, e)) = stack.last() {
+        let adj = g.adj(v);
+        while next[v] < adj.len() && used[adj[next[v]].1] {
+            next[v] += 1;
+        }
+        if next[v] < adj.len() {
+            let (to, id) = adj[next[v]];
+            used[id] = true;
+            stack.push((to, id));
+        } else {
+            stack.pop();
+            vertices.push(v);
+            edges.push(e);
+        }
+    }
+
+    // Some edge is not reachable from s
+    if edges.len() != m + 1 {
+        return None;
+    }
+    vertices.reverse();
+    edges.pop();
+    edges.reverse();
+    Some((vertices, edges))
+}
+
+#[test]
+fn test_eulerian_path_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    // Whether there is a trail from v using every unused edge
+    fn brute<W>(g: &Graph<W>, v: usize, used: &mut [bool], rest: usize) -> bool {
+        if rest == 0 {
+            return true;
+        }
+        for &(to, id) in g.adj(v) {
+            if !used[id] {
+                used[id] = true;
+                let ok = brute(g, to, used, rest - 1);
+                used[id] = false;
+                if ok {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    for _ in 0..2000 {
+        let n = rng.next_u32() as usize % 5 + 1;
+        let m = rng.next_u32() as usize % 8;
+        let directed = rng.gen();
+        let edges = (0..m)
+            .map(|_| (rng.next_u32() as usize % n, rng.next_u32() as usize % n, ()))
+            .collect::<Vec<_>>();
+        let g = Graph::new(n, edges.clone(), directed);
+
+        let check = |res: &Option<(Vec<usize>, Vec<usize>)>, s: Option<usize>| {
+            let possible = (0..n)
+                .filter(|&v| s.map(|s| s == v).unwrap_or(true))
+                .any(|v| brute(&g, v, &mut vec![false; m], m));
+            assert_eq!(res.is_some(), possible);
+            if let Some((vs, es)) = res {
+                assert_eq!(vs.len(), m + 1);
+                if let Some(s) = s {
+                    assert_eq!(vs[0], s);
+                }
+                let mut sorted = es.clone();
+                sorted.sort();
+                assert_eq!(sorted, (0..m).collect::<Vec<_>>());
+                for (i, &id) in es.iter().enumerate() {
+                    let (u, v, _) = edges[id];
+                    assert!(
+                        (u, v) == (vs[i], vs[i + 1]) || !directed && (v, u) == (vs[i], vs[i + 1])
+                    );
+                }
+            }
+        };
+
+        check(&eulerian_path(&g, None), None);
+        let s = rng.next_u32() as usize % n;
+        check(&eulerian_path(&g, Some(s)), Some(s));
+    }
+
+    // Large circuit
+    let n = 1_000_000;
+    let g = Graph::unweighted(
+        n,
+        &(0..n).map(|i| (i, (i + 1) % n)).collect::<Vec<_>>(),
+        true,
+    );
+    let (vs, es) = eulerian_path(&g, Some(3)).unwrap();
+    assert_eq!(vs.len(), n + 1);
+    assert_eq!(es[0], 3);
+    assert_eq!(vs[n], 3);
+}
@@ -1,69 +1,193 @@
+use crate::graph::Graph;
+use crate::shortest_path::Cost;
 use cargo_snippet::snippet;
-use std::cmp::min;
-use std::collections::{BTreeSet, VecDeque};
+use std::cmp::{min, Reverse};
+use std::collections::{BTreeSet, BinaryHeap, VecDeque};
 
 #[snippet("Flow")]
+/// Capacity of `Flow`
+pub trait Capacity:
+    Copy + Ord + std::ops::Add<Output = Self> + std::ops::Sub<Output = Self>
+{
+    fn zero() -> Self;
+    fn max_value() -> Self;
+    fn half(self) -> Self;
+}
+
+#[snippet("Flow")]
+macro_rules! impl_capacity {
+    ($($t:ty),*) => {
+        $(impl Capacity for $t {
+            fn zero() -> Self {
+                0
+            }
+            fn max_value() -> Self {
+                <$t>::max_value()
+            }
+            fn half(self) -> Self {
+                self / 2
+            }
+        })*
+    };
+}
+
+#[snippet("Flow")]
+impl_capacity!(i32, i64, u32, u64, usize);
+
+#[snippet("Flow")]
+#[derive(Clone, Debug)]
 #[allow(dead_code)]
 /// Struct for maximum flow problem
-pub struct Flow {
-    /// to, capacity, rev
-    edges: Vec<Vec<(usize, usize, usize)>>,
+pub struct Flow<C> {
+    /// to, residual capacity, rev
+    edges: Vec<Vec<(usize, C, usize)>>,
+    /// from and index in `edges[from]` of each added edge
+    pos: Vec<(usize, usize)>,
 }
 
 #[snippet("Flow")]
-impl Flow {
+impl<C: Capacity> Flow<C> {
     #[allow(dead_code)]
-    pub fn new(max_size: usize) -> Flow {
+    pub fn new(max_size: usize) -> Flow<C> {
         Flow {
             edges: vec![Vec::new(); max_size + 1],
+            pos: Vec::new(),
         }
     }
 
     #[allow(dead_code)]
-    pub fn add_edge(&mut self, from: usize, to: usize, cap: usize) {
-        let from_rev = self.edges[to].len();
+    /// Returns the id of the edge
+    pub fn add_edge(&mut self, from: usize, to: usize, cap: C) -> usize {
+        let from_rev = self.edges[to].len() + (from == to) as usize;
         let to_rev = self.edges[from].len();
 
+        self.pos.push((from, to_rev));
         self.edges[from].push((to, cap, from_rev));
-        self.edges[to].push((from, 0, to_rev));
+        self.edges[to].push((from, C::zero(), to_rev));
+        self.pos.len() - 1
     }
 
     #[allow(dead_code)]
-    /// Calculate maximum flow by dinic's algorithm
-    pub fn max_flow_dinic(&mut self, s: usize, t: usize) -> usize {
-        let mut flow = 0;
+    /// (from, to, capacity, flow)
+    pub fn get_edge(&self, id: usize) -> (usize, usize, C, C) {
+        let (from, i) = self.pos[id];
+        let (to, cap, rev) = self.edges[from][i];
+        let flow = self.edges[to][rev].1;
+        (from, to, cap + flow, flow)
+    }
+
+    #[allow(dead_code)]
+    /// Set the capacity and the flow of an edge. `flow` must not exceed `cap`.
+    /// Flow conservation is up to the caller.
+    pub fn change_edge(&mut self, id: usize, cap: C, flow: C) {
+        assert!(flow <= cap);
+        let (from, i) = self.pos[id];
+        let (to, _, rev) = self.edges[from][i];
+        self.edges[from][i].1 = cap - flow;
+        self.edges[to][rev].1 = flow;
+    }
+
+    #[allow(dead_code)]
+    /// Calculate maximum flow by dinic's algorithm, without recursion
+    pub fn max_flow_dinic(&mut self, s: usize, t: usize) -> C {
+        if s == t {
+            return C::zero();
+        }
+        let mut flow = C::zero();
         loop {
-            let level = self.bfs_dinic(s);
-            let mut iter = vec![0; self.edges.len()];
+            let mut level = self.levels(s, C::zero());
             if level[t].is_none() {
                 return flow;
             }
+            flow = flow + self.blocking_flow(s, t, C::zero(), &mut level);
+        }
+    }
 
-            loop {
-                let f = self.dfs_dinic(s, t, usize::max_value(), &level, &mut iter);
-                if f == 0 {
+    #[allow(dead_code)]
+    /// Calculate maximum flow by Ford-Fulkerson algorithm, without recursion
+    pub fn max_flow_ff(&mut self, s: usize, t: usize) -> C {
+        if s == t {
+            return C::zero();
+        }
+        let n = self.edges.len();
+        let mut flow = C::zero();
+        loop {
+            // (vertex, edge index) used to enter each vertex
+            let mut prev: Vec<Option<(usize, usize)>> = vec![None; n];
+            let mut used = vec![false; n];
+            used[s] = true;
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                if v == t {
                     break;
                 }
-                flow += f;
+                for (i, &(to, cap, _)) in self.edges[v].iter().enumerate() {
+                    if cap > C::zero() && !used[to] {
+                        used[to] = true;
+                        prev[to] = Some((v, i));
+                        stack.push(to);
+                    }
+                }
+            }
+            if !used[t] {
+                return flow;
+            }
+
+            let mut f = C::max_value();
+            let mut v = t;
+            while let Some((u, i)) = prev[v] {
+                f = min(f, self.edges[u][i].1);
+                v = u;
             }
+            let mut v = t;
+            while let Some((u, i)) = prev[v] {
+                let (to, _, rev) = self.edges[u][i];
+                self.edges[u][i].1 = self.edges[u][i].1 - f;
+                self.edges[to][rev].1 = self.edges[to][rev].1 + f;
+                v = u;
+            }
+            flow = flow + f;
         }
     }
 
     #[allow(dead_code)]
-    fn bfs_dinic(&self, s: usize) -> Vec<Option<usize>> {
+    /// Dinic's algorithm with capacity scaling, without recursion. O(E V log U)
+    /// Usually slower than `max_flow_dinic` in practice.
+    pub fn max_flow_scaling(&mut self, s: usize, t: usize) -> C {
+        if s == t {
+            return C::zero();
+        }
+        let mut flow = C::zero();
+        let mut delta = self
+            .edges
+            .iter()
+            .flatten()
+            .map(|e| e.1)
+            .max()
+            .unwrap_or_else(C::zero);
+        while delta > C::zero() {
+            loop {
+                let mut level = self.levels(s, delta);
+                if level[t].is_none() {
+                    break;
+                }
+                flow = flow + self.blocking_flow(s, t, delta, &mut level);
+            }
+            delta = delta.half();
+        }
+        flow
+    }
+
+    /// BFS levels over edges whose residual capacity is positive and at least `delta`
+    fn levels(&self, s: usize, delta: C) -> Vec<Option<usize>> {
         let mut level = vec![None; self.edges.len()];
         let mut que = VecDeque::new();
-
         level[s] = Some(0);
         que.push_back(s);
-
-        while !que.is_empty() {
-            let v = que.pop_front().unwrap();
-            let l = Some(level[v].unwrap() + 1);
-
+        while let Some(v) = que.pop_front() {
             for &(to, cap, _) in &self.edges[v] {
-                if cap > 0 && level[to].is_none() {
-                    level[to] = l;
+                if cap > C::zero() && cap >= delta && level[to].is_none() {
+                    level[to] = Some(level[v].unwrap() + 1);
                     que.push_back(to);
                 }
             }
@@ -71,81 +195,186 @@ impl Flow {
         level
     }
 
-    #[allow(dead_code)]
-    fn dfs_dinic(
-        &mut self,
-        v: usize,
-        t: usize,
-        f: usize,
-        level: &[Option<usize>],
-        iter: &mut [usize],
-    ) -> usize {
-        if v == t {
-            return f;
-        }
-
-        for i in iter[v]..self.edges[v].len() {
-            iter[v] = i;
+    /// Augment along level-increasing paths until `t` is unreachable. Dead ends are removed from `level`
+    fn blocking_flow(&mut self, s: usize, t: usize, delta: C, level: &mut [Option<usize>]) -> C {
+        let mut total = C::zero();
+        let mut iter = vec![0; self.edges.len()];
+        // (vertex, edge index) of the current path
+        let mut path: Vec<(usize, usize)> = Vec::new();
+        let mut v = s;
+        loop {
+            if v == t {
+                let f = path.iter().map(|&(u, i)| self.edges[u][i].1).min().unwrap();
+                for &(u, i) in &path {
+                    let (to, _, rev) = self.edges[u][i];
+                    self.edges[u][i].1 = self.edges[u][i].1 - f;
+                    self.edges[to][rev].1 = self.edges[to][rev].1 + f;
+                }
+                total = total + f;
+                // Retreat to the tail of the first saturated edge
+                let k = path
+                    .iter()
+                    .position(|&(u, i)| {
+                        let cap = self.edges[u][i].1;
+                        cap == C::zero() || cap < delta
+                    })
+                    .unwrap();
+                v = path[k].0;
+                path.truncate(k);
+                continue;
+            }
 
-            let (to, cap, rev) = self.edges[v][i];
-            if cap > 0 && level[v].unwrap() < level[to].unwrap_or(0) {
-                let d = self.dfs_dinic(to, t, min(f, cap), level, iter);
-                if d > 0 {
-                    self.edges[v][i].1 -= d;
-                    self.edges[to][rev].1 += d;
-                    return d;
+            let next = level[v].map(|l| l + 1);
+            let mut advanced = false;
+            while iter[v] < self.edges[v].len() {
+                let (to, cap, _) = self.edges[v][iter[v]];
+                if cap > C::zero() && cap >= delta && level[to] == next {
+                    path.push((v, iter[v]));
+                    v = to;
+                    advanced = true;
+                    break;
+                }
+                iter[v] += 1;
+            }
+            if !advanced {
+                if v == s {
+                    return total;
                 }
+                // Dead end
+                level[v] = None;
+                let (u, _) = path.pop().unwrap();
+                iter[u] += 1;
+                v = u;
             }
         }
-        0
     }
 
     #[allow(dead_code)]
-    fn max_flow_ff(&mut self, s: usize, t: usize) -> usize {
-        let mut flow = 0;
-        let l = self.edges.len();
-        loop {
-            let f = self.dfs_ff(s, t, usize::max_value(), &mut vec![false; l]);
-            if f == 0 {
-                break;
+    /// Highest-label push-relabel with the gap heuristic. O(V^3)
+    /// Each gap scans all vertices in O(V). Fast on dense graphs. Edge flows form a valid flow afterwards.
+    pub fn max_flow_push_relabel(&mut self, s: usize, t: usize) -> C {
+        let n = self.edges.len();
+        if s == t {
+            return C::zero();
+        }
+
+        // Initial heights are distances to t
+        let mut h = vec![n + 1; n];
+        h[t] = 0;
+        let mut que = VecDeque::new();
+        que.push_back(t);
+        while let Some(v) = que.pop_front() {
+            for &(to, _, rev) in &self.edges[v] {
+                if h[to] == n + 1 && to != s && self.edges[to][rev].1 > C::zero() {
+                    h[to] = h[v] + 1;
+                    que.push_back(to);
+                }
             }
-            flow += f;
         }
-        flow
-    }
+        h[s] = n;
 
-    #[allow(dead_code)]
-    fn dfs_ff(&mut self, v: usize, t: usize, f: usize, used: &mut [bool]) -> usize {
-        if v == t {
-            return f;
+        let mut count = vec![0; 2 * n];
+        for &x in &h {
+            count[x] += 1;
         }
-        used[v] = true;
+        let mut excess = vec![C::zero(); n];
+        let mut buckets = vec![Vec::new(); 2 * n];
+        let mut cur = vec![0; n];
+        let mut highest = 0;
 
-        for i in 0..self.edges[v].len() {
-            let (to, cap, rev) = self.edges[v][i];
-            if !used[to] && cap > 0 {
-                let d = self.dfs_ff(to, t, min(f, cap), used);
-                if d > 0 {
-                    self.edges[v][i].1 -= d;
-                    self.edges[to][rev].1 += d;
-                    return d;
+        for i in 0..self.edges[s].len() {
+            let (to, cap, rev) = self.edges[s][i];
+            if cap > C::zero() {
+                self.edges[s][i].1 = C::zero();
+                self.edges[to][rev].1 = self.edges[to][rev].1 + cap;
+                if to != t && to != s && excess[to] == C::zero() {
+                    buckets[h[to]].push(to);
+                    highest = highest.max(h[to]);
                 }
+                excess[to] = excess[to] + cap;
             }
         }
-        0
+
+        loop {
+            let v = match buckets[highest].pop() {
+                Some(v) => v,
+                None if highest == 0 => break,
+                None => {
+                    highest -= 1;
+                    continue;
+                }
+            };
+            if h[v] != highest || excess[v] == C::zero() {
+                continue;
+            }
+
+            // Discharge v
+            while excess[v] > C::zero() {
+                if cur[v] == self.edges[v].len() {
+                    // Relabel
+                    let old = h[v];
+                    count[old] -= 1;
+                    h[v] = self.edges[v]
+                        .iter()
+                        .filter(|e| e.1 > C::zero())
+                        .map(|e| h[e.0] + 1)
+                        .min()
+                        .unwrap_or(2 * n - 1)
+                        .min(2 * n - 1);
+                    count[h[v]] += 1;
+                    cur[v] = 0;
+
+                    if count[old] == 0 && old < n {
+                        // Gap: vertices above `old` cannot reach t
+                        for u in 0..n {
+                            if old < h[u] && h[u] < n {
+                                count[h[u]] -= 1;
+                                h[u] = n + 1;
+                                count[h[u]] += 1;
+                                cur[u] = 0;
+                                if u != s && u != t && u != v && excess[u] > C::zero() {
+                                    buckets[h[u]].push(u);
+                                    highest = highest.max(h[u]);
+                                }
+                            }
+                        }
+                    }
+                    buckets[h[v]].push(v);
+                    highest = highest.max(h[v]);
+                    break;
+                }
+
+                let (to, cap, rev) = self.edges[v][cur[v]];
+                if cap > C::zero() && h[v] == h[to] + 1 {
+                    let d = min(excess[v], cap);
+                    self.edges[v][cur[v]].1 = cap - d;
+                    self.edges[to][rev].1 = self.edges[to][rev].1 + d;
+                    excess[v] = excess[v] - d;
+                    if to != s && to != t && excess[to] == C::zero() {
+                        buckets[h[to]].push(to);
+                    }
+                    excess[to] = excess[to] + d;
+                } else {
+                    cur[v] += 1;
+                }
+            }
+        }
+
+        excess[t]
     }
 
     #[allow(dead_code)]
-    fn cut(&self, s: usize) -> BTreeSet<usize> {
+    /// Vertices reachable from `s` in the residual network.
+    /// After a maximum flow from `s`, this is the source side of a minimum cut.
+    pub fn cut(&self, s: usize) -> BTreeSet<usize> {
         let mut stack = Vec::new();
         let mut ss = BTreeSet::new();
         ss.insert(s);
         stack.push(s);
 
-        while !stack.is_empty() {
-            let v = stack.pop().unwrap();
+        while let Some(v) = stack.pop() {
             for &(to, cap, _) in &self.edges[v] {
-                if cap > 0 && !ss.contains(&to) {
+                if cap > C::zero() && !ss.contains(&to) {
                     ss.insert(to);
                     stack.push(to);
                 }
@@ -154,6 +383,373 @@ impl Flow {
 
         ss
     }
+
+    #[allow(dead_code)]
+    /// Edges (from, to, capacity) from the source side to the sink side of a minimum cut.
+    /// Call after a maximum flow from `s`. The capacities sum up to the flow.
+    pub fn cut_edges(&self, s: usize) -> Vec<(usize, usize, C)> {
+        let ss = self.cut(s);
+        (0..self.pos.len())
+            .map(|id| self.get_edge(id))
+            .filter(|&(from, to, _, _)| ss.contains(&from) && !ss.contains(&to))
+            .map(|(from, to, cap, _)| (from, to, cap))
+            .collect()
+    }
+}
+
+#[snippet("LowerBoundFlow")]
+#[snippet(include = "Flow")]
+#[allow(dead_code)]
+/// Flow with lower bounds on edges, by the reduction to a maximum flow with a super source and sink.
+/// Call one of `feasible`, `max_flow` and `min_flow` once.
+pub struct LowerBoundFlow<C> {
+    n: usize,
+    pub flow: Flow<C>,
+    lower: Vec<C>,
+    /// Sum of lower bounds of incoming and outgoing edges
+    demand: Vec<(C, C)>,
+}
+
+#[snippet("LowerBoundFlow")]
+#[allow(dead_code)]
+impl<C: Capacity> LowerBoundFlow<C> {
+    /// Vertices 0..n
+    pub fn new(n: usize) -> LowerBoundFlow<C> {
+        LowerBoundFlow {
+            n,
+            flow: Flow::new(n + 2),
+            lower: Vec::new(),
+            demand: vec![(C::zero(), C::zero()); n],
+        }
+    }
+
+    /// Edge whose flow must be in [lower, upper]. Returns the id of the edge
+    pub fn add_edge(&mut self, from: usize, to: usize, lower: C, upper: C) -> usize {
+        assert!(lower <= upper);
+        self.demand[to].0 = self.demand[to].0 + lower;
+        self.demand[from].1 = self.demand[from].1 + lower;
+        self.lower.push(lower);
+        self.flow.add_edge(from, to, upper - lower)
+    }
+
+    /// (from, to, lower, upper, flow)
+    pub fn get_edge(&self, id: usize) -> (usize, usize, C, C, C) {
+        let (from, to, cap, flow) = self.flow.get_edge(id);
+        let l = self.lower[id];
+        (from, to, l, cap + l, flow + l)
+    }
+
+    /// Whether a circulation satisfying every bound exists
+    pub fn feasible(&mut self) -> bool {
+        let (ss, tt) = (self.n, self.n + 1);
+        let mut need = C::zero();
+        for v in 0..self.n {
+            let (i, o) = self.demand[v];
+            if i > o {
+                self.flow.add_edge(ss, v, i - o);
+                need = need + (i - o);
+            } else if o > i {
+                self.flow.add_edge(v, tt, o - i);
+            }
+        }
+        self.flow.max_flow_dinic(ss, tt) == need
+    }
+
+    /// Connect s and t both ways, find a feasible flow and remove the connections.
+    /// Returns the flows that went through t -> s and s -> t.
+    fn feasible_st(&mut self, s: usize, t: usize) -> Option<(C, C)> {
+        let ts = self.flow.add_edge(t, s, C::max_value());
+        let st = self.flow.add_edge(s, t, C::max_value());
+        if !self.feasible() {
+            return None;
+        }
+        let (_, _, _, x) = self.flow.get_edge(ts);
+        let (_, _, _, y) = self.flow.get_edge(st);
+        self.flow.change_edge(ts, C::zero(), C::zero());
+        self.flow.change_edge(st, C::zero(), C::zero());
+        Some((x, y))
+    }
+
+    /// Maximum flow from `s` to `t` satisfying every bound, or `None` if infeasible.
+    /// The flow is negative if flow must go from `t` to `s`, so use a signed `C` then.
+    pub fn max_flow(&mut self, s: usize, t: usize) -> Option<C> {
+        let (x, y) = self.feasible_st(s, t)?;
+        Some(x + self.flow.max_flow_dinic(s, t) - y)
+    }
+
+    /// Minimum flow from `s` to `t` satisfying every bound, or `None` if infeasible.
+    /// The flow is negative if flow must go from `t` to `s`, so use a signed `C` then.
+    pub fn min_flow(&mut self, s: usize, t: usize) -> Option<C> {
+        let (x, y) = self.feasible_st(s, t)?;
+        Some(x - (y + self.flow.max_flow_dinic(t, s)))
+    }
+}
+
+#[snippet("stoer_wagner")]
+#[snippet(include = "Cost")]
+#[snippet(include = "Graph")]
+#[allow(dead_code)]
+/// Global minimum cut of an undirected graph with non-negative weights. O(V^3)
+/// Returns (weight, vertices of one side), or `None` if there are less than 2 vertices.
+pub fn stoer_wagner<C: Cost>(g: &Graph<C>) -> Option<(C, Vec<usize>)> {
+    let n = g.len();
+    if n < 2 {
+        return None;
+    }
+    let mut w = vec![vec![C::zero(); n]; n];
+    for &(u, v, c) in &g.edges {
+        if u != v {
+            w[u][v] = w[u][v].add(c);
+            w[v][u] = w[v][u].add(c);
+        }
+    }
+
+    // Original vertices merged into each vertex
+    let mut groups = (0..n).map(|v| vec![v]).collect::<Vec<_>>();
+    let mut alive = (0..n).collect::<Vec<_>>();
+    let mut best: Option<(C, Vec<usize>)> = None;
+
+    while alive.len() > 1 {
+        let mut added = vec![false; n];
+        let mut key = vec![C::zero(); n];
+        let mut prev = alive[0];
+        let mut last = alive[0];
+        for _ in 0..alive.len() {
+            prev = last;
+            last = *alive
+                .iter()
+                .filter(|&&v| !added[v])
+                .max_by_key(|&&v| key[v])
+                .unwrap();
+            added[last] = true;
+            for &v in &alive {
+                if !added[v] {
+                    key[v] = key[v].add(w[last][v]);
+                }
+            }
+        }
+
+        // Cut of the phase separates `last` from the rest
+        if best.as_ref().map(|b| key[last] < b.0).unwrap_or(true) {
+            best = Some((key[last], groups[last].clone()));
+        }
+
+        let moved = std::mem::take(&mut groups[last]);
+        groups[prev].extend(moved);
+        for &v in &alive {
+            let x = w[last][v];
+            w[prev][v] = w[prev][v].add(x);
+            w[v][prev] = w[prev][v];
+        }
+        w[prev][prev] = C::zero();
+        alive.retain(|&v| v != last);
+    }
+
+    best
+}
+
+#[snippet("GomoryHu")]
+#[snippet(include = "Flow")]
+#[allow(dead_code)]
+/// Gusfield's equivalent flow tree. V - 1 maximum flow calculations.
+/// The minimum cut value between u and v is the minimum weight on the tree path.
+/// Unlike a true Gomory-Hu cut tree, removing a tree edge does not give the cut itself.
+pub struct GomoryHu<C: Capacity> {
+    /// Parent of each vertex. parent[v] < v for v > 0
+    pub parent: Vec<usize>,
+    /// Weight of the edge (parent[v], v)
+    pub weight: Vec<C>,
+    depth: Vec<usize>,
+}
+
+#[snippet("GomoryHu")]
+impl<C: Capacity> GomoryHu<C> {
+    #[allow(dead_code)]
+    /// Undirected edges (u, v, capacity) on vertices 0..n
+    pub fn new(n: usize, edges: &[(usize, usize, C)]) -> GomoryHu<C> {
+        let mut parent = vec![0; n];
+        let mut weight = vec![C::zero(); n];
+        for i in 1..n {
+            let mut flow = Flow::new(n);
+            for &(u, v, c) in edges {
+                flow.add_edge(u, v, c);
+                flow.add_edge(v, u, c);
+            }
+            weight[i] = flow.max_flow_dinic(i, parent[i]);
+            let side = flow.cut(i);
+            for j in i + 1..n {
+                if side.contains(&j) && parent[j] == parent[i] {
+                    parent[j] = i;
+                }
+            }
+        }
+
+        let mut depth = vec![0; n];
+        for v in 1..n {
+            depth[v] = depth[parent[v]] + 1;
+        }
+        GomoryHu {
+            parent,
+            weight,
+            depth,
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Minimum cut between `u` and `v`. O(V)
+    pub fn min_cut(&self, mut u: usize, mut v: usize) -> C {
+        let mut res = C::max_value();
+        while u != v {
+            if self.depth[u] < self.depth[v] {
+                std::mem::swap(&mut u, &mut v);
+            }
+            res = res.min(self.weight[u]);
+            u = self.parent[u];
+        }
+        res
+    }
+}
+
+#[snippet("MinCostFlow")]
+#[snippet(include = "Flow")]
+#[allow(dead_code)]
+/// Minimum cost flow by successive shortest paths with Dijkstra and potentials.
+/// Negative costs are allowed as long as there is no negative cycle.
+/// Capacities and costs share a signed type such as i64.
+pub struct MinCostFlow<T> {
+    /// to, capacity, cost, rev
+    edges: Vec<Vec<(usize, T, T, usize)>>,
+    /// from and index in `edges[from]` of each added edge
+    pos: Vec<(usize, usize)>,
+    has_negative: bool,
+}
+
+#[snippet("MinCostFlow")]
+#[allow(dead_code)]
+impl<T: Capacity + std::ops::Neg<Output = T> + std::ops::Mul<Output = T>> MinCostFlow<T> {
+    pub fn new(n: usize) -> MinCostFlow<T> {
+        MinCostFlow {
+            edges: vec![Vec::new(); n],
+            pos: Vec::new(),
+            has_negative: false,
+        }
+    }
+
+    /// Returns the id of the edge
+    pub fn add_edge(&mut self, from: usize, to: usize, cap: T, cost: T) -> usize {
+        let from_rev = self.edges[to].len() + (from == to) as usize;
+        let to_rev = self.edges[from].len();
+        self.has_negative |= cost < T::zero();
+        self.pos.push((from, to_rev));
+        self.edges[from].push((to, cap, cost, from_rev));
+        self.edges[to].push((from, T::zero(), -cost, to_rev));
+        self.pos.len() - 1
+    }
+
+    /// (from, to, capacity, flow, cost)
+    pub fn get_edge(&self, id: usize) -> (usize, usize, T, T, T) {
+        let (from, i) = self.pos[id];
+        let (to, cap, cost, rev) = self.edges[from][i];
+        let flow = self.edges[to][rev].1;
+        (from, to, cap + flow, flow, cost)
+    }
+
+    /// Sends at most `limit` units from `s` to `t` with the minimum cost.
+    /// Returns (flow, cost)
+    pub fn flow(&mut self, s: usize, t: usize, limit: T) -> (T, T) {
+        *self.slope(s, t, limit).last().unwrap()
+    }
+
+    /// Breakpoints (flow, cost) of the minimum cost as a function of the flow.
+    /// The function is convex and linear between consecutive points.
+    pub fn slope(&mut self, s: usize, t: usize, limit: T) -> Vec<(T, T)> {
+        let n = self.edges.len();
+        let mut h = vec![T::zero(); n];
+        if self.has_negative {
+            // Bellman-Ford from s over edges with capacity
+            let mut dist = vec![None; n];
+            dist[s] = Some(T::zero());
+            for _ in 0..n {
+                let mut updated = false;
+                for v in 0..n {
+                    if let Some(d) = dist[v] {
+                        for &(to, cap, cost, _) in &self.edges[v] {
+                            if cap > T::zero() && dist[to].map(|x| d + cost < x).unwrap_or(true) {
+                                dist[to] = Some(d + cost);
+                                updated = true;
+                            }
+                        }
+                    }
+                }
+                if !updated {
+                    break;
+                }
+            }
+            h = dist
+                .into_iter()
+                .map(|d| d.unwrap_or_else(T::zero))
+                .collect();
+        }
+
+        let mut res = vec![(T::zero(), T::zero())];
+        let (mut flow, mut cost) = (T::zero(), T::zero());
+        let mut prev_slope = None;
+        let mut prev = vec![(0, 0); n];
+        while flow < limit {
+            // Dijkstra on reduced costs
+            let mut dist = vec![None; n];
+            let mut heap = BinaryHeap::new();
+            dist[s] = Some(T::zero());
+            heap.push(Reverse((T::zero(), s)));
+            while let Some(Reverse((d, v))) = heap.pop() {
+                if dist[v] != Some(d) {
+                    continue;
+                }
+                for (i, &(to, cap, c, _)) in self.edges[v].iter().enumerate() {
+                    let nd = d + c + h[v] - h[to];
+                    if cap > T::zero() && dist[to].map(|x| nd < x).unwrap_or(true) {
+                        dist[to] = Some(nd);
+                        prev[to] = (v, i);
+                        heap.push(Reverse((nd, to)));
+                    }
+                }
+            }
+            if dist[t].is_none() {
+                break;
+            }
+            for v in 0..n {
+                if let Some(d) = dist[v] {
+                    h[v] = h[v] + d;
+                }
+            }
+
+            let mut f = limit - flow;
+            let mut v = t;
+            while v != s {
+                let (u, i) = prev[v];
+                f = min(f, self.edges[u][i].1);
+                v = u;
+            }
+            let mut v = t;
+            while v != s {
+                let (u, i) = prev[v];
+                let rev = self.edges[u][i].3;
+                self.edges[u][i].1 = self.edges[u][i].1 - f;
+                self.edges[v][rev].1 = self.edges[v][rev].1 + f;
+                v = u;
+            }
+
+            let unit = h[t] - h[s];
+            flow = flow + f;
+            cost = cost + f * unit;
+            if prev_slope == Some(unit) {
+                res.pop();
+            }
+            res.push((flow, cost));
+            prev_slope = Some(unit);
+        }
+        res
+    }
 }
 
 #[snippet("bipartite_matching")]
@@ -191,6 +787,180 @@ pub fn bipartite_matching(g: &[Vec<usize>]) -> usize {
     res
 }
 
+#[snippet("HopcroftKarp")]
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+/// Maximum bipartite matching by Hopcroft-Karp algorithm without recursion. O(E sqrt(V))
+pub struct HopcroftKarp {
+    g: Vec<Vec<usize>>,
+    /// Partner of each left vertex
+    pub mate_left: Vec<Option<usize>>,
+    /// Partner of each right vertex
+    pub mate_right: Vec<Option<usize>>,
+}
+
+#[snippet("HopcroftKarp")]
+#[allow(dead_code)]
+impl HopcroftKarp {
+    /// Left vertices 0..left and right vertices 0..right
+    pub fn new(left: usize, right: usize) -> HopcroftKarp {
+        HopcroftKarp {
+            g: vec![Vec::new(); left],
+            mate_left: vec![None; left],
+            mate_right: vec![None; right],
+        }
+    }
+
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        self.g[u].push(v);
+    }
+
+    /// Returns matched pairs (left, right)
+    pub fn solve(&mut self) -> Vec<(usize, usize)> {
+        let n = self.g.len();
+        loop {
+            // Layers of left vertices by alternating BFS from free ones
+            let mut dist = vec![!0; n];
+            let mut que = VecDeque::new();
+            for (u, d) in dist.iter_mut().enumerate() {
+                if self.mate_left[u].is_none() {
+                    *d = 0;
+                    que.push_back(u);
+                }
+            }
+            let mut found = false;
+            while let Some(u) = que.pop_front() {
+                for &v in &self.g[u] {
+                    match self.mate_right[v] {
+                        None => found = true,
+                        Some(w) if dist[w] == !0 => {
+                            dist[w] = dist[u] + 1;
+                            que.push_back(w);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if !found {
+                break;
+            }
+
+            let mut iter = vec![0; n];
+            for s in 0..n {
+                if self.mate_left[s].is_some() {
+                    continue;
+                }
+                let mut stack = vec![s];
+                while let Some(&u) = stack.last() {
+                    if iter[u] == self.g[u].len() {
+                        dist[u] = !0;
+                        stack.pop();
+                        if let Some(&p) = stack.last() {
+                            iter[p] += 1;
+                        }
+                        continue;
+                    }
+                    let v = self.g[u][iter[u]];
+                    match self.mate_right[v] {
+                        None => {
+                            for &x in &stack {
+                                let y = self.g[x][iter[x]];
+                                self.mate_left[x] = Some(y);
+                                self.mate_right[y] = Some(x);
+                            }
+                            break;
+                        }
+                        Some(w) if dist[w] == dist[u] + 1 => stack.push(w),
+                        _ => iter[u] += 1,
+                    }
+                }
+            }
+        }
+
+        self.mate_left
+            .iter()
+            .enumerate()
+            .filter_map(|(u, v)| v.map(|v| (u, v)))
+            .collect()
+    }
+
+    /// Minimum vertex cover (left vertices, right vertices) by König's theorem.
+    /// Call after `solve`.
+    pub fn min_vertex_cover(&self) -> (Vec<usize>, Vec<usize>) {
+        let (visited_left, visited_right) = self.alternating_reach();
+        (
+            (0..self.g.len()).filter(|&u| !visited_left[u]).collect(),
+            (0..self.mate_right.len())
+                .filter(|&v| visited_right[v])
+                .collect(),
+        )
+    }
+
+    /// Maximum independent set (left vertices, right vertices). Call after `solve`.
+    pub fn max_independent_set(&self) -> (Vec<usize>, Vec<usize>) {
+        let (visited_left, visited_right) = self.alternating_reach();
+        (
+            (0..self.g.len()).filter(|&u| visited_left[u]).collect(),
+            (0..self.mate_right.len())
+                .filter(|&v| !visited_right[v])
+                .collect(),
+        )
+    }
+
+    /// Vertices reachable from free left vertices by alternating paths
+    fn alternating_reach(&self) -> (Vec<bool>, Vec<bool>) {
+        let mut visited_left = vec![false; self.g.len()];
+        let mut visited_right = vec![false; self.mate_right.len()];
+        let mut stack = (0..self.g.len())
+            .filter(|&u| self.mate_left[u].is_none())
+            .collect::<Vec<_>>();
+        for &u in &stack {
+            visited_left[u] = true;
+        }
+        while let Some(u) = stack.pop() {
+            for &v in &self.g[u] {
+                if !visited_right[v] {
+                    visited_right[v] = true;
+                    if let Some(w) = self.mate_right[v] {
+                        if !visited_left[w] {
+                            visited_left[w] = true;
+                            stack.push(w);
+                        }
+                    }
+                }
+            }
+        }
+        (visited_left, visited_right)
+    }
+}
+
+#[snippet("dag_min_path_cover")]
+#[snippet(include = "HopcroftKarp")]
+#[allow(dead_code)]
+/// Minimum number of vertex-disjoint paths covering every vertex of a DAG.
+/// Returns the paths. For paths that may share vertices, pass the transitive closure.
+pub fn dag_min_path_cover(g: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = g.len();
+    let mut hk = HopcroftKarp::new(n, n);
+    for (u, es) in g.iter().enumerate() {
+        for &v in es {
+            hk.add_edge(u, v);
+        }
+    }
+    hk.solve();
+
+    (0..n)
+        .filter(|&v| hk.mate_right[v].is_none())
+        .map(|s| {
+            let mut path = vec![s];
+            while let Some(v) = hk.mate_left[path[path.len() - 1]] {
+                path.push(v);
+            }
+            path
+        })
+        .collect()
+}
+
 #[test]
 fn test_flow() {
     let mut flow = Flow::new(10);
@@ -208,6 +978,176 @@ fn test_flow() {
     assert_eq!(flow.max_flow_dinic(s, t), 11);
 }
 
+#[test]
+fn test_flow_edges() {
+    let mut flow: Flow<u64> = Flow::new(4);
+    let a = flow.add_edge(0, 1, 5);
+    let b = flow.add_edge(1, 2, 3);
+    let c = flow.add_edge(0, 2, 1);
+    let d = flow.add_edge(2, 2, 7);
+    assert_eq!(flow.max_flow_dinic(0, 2), 4);
+    assert_eq!(flow.get_edge(a), (0, 1, 5, 3));
+    assert_eq!(flow.get_edge(b), (1, 2, 3, 3));
+    assert_eq!(flow.get_edge(c), (0, 2, 1, 1));
+    assert_eq!(flow.get_edge(d), (2, 2, 7, 0));
+    assert_eq!(flow.cut_edges(0), vec![(1, 2, 3), (0, 2, 1)]);
+
+    flow.change_edge(b, 10, 3);
+    assert_eq!(flow.max_flow_dinic(0, 2), 2);
+    assert_eq!(flow.get_edge(a), (0, 1, 5, 5));
+    assert_eq!(flow.get_edge(b), (1, 2, 10, 5));
+}
+
+#[test]
+fn test_lower_bound_flow_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..300 {
+        let n = rng.next_u32() as usize % 4 + 2;
+        let m = rng.next_u32() as usize % 6 + 1;
+        let edges = (0..m)
+            .map(|_| {
+                let l = rng.next_u32() as i64 % 2;
+                (
+                    rng.next_u32() as usize % n,
+                    rng.next_u32() as usize % n,
+                    l,
+                    l + rng.next_u32() as i64 % 3,
+                )
+            })
+            .collect::<Vec<_>>();
+        let (s, t) = (0, n - 1);
+
+        // Net flow out of s of every flow conserved at all vertices but s and t
+        let mut values = Vec::new();
+        let mut circulation = false;
+        let mut fs = edges.iter().map(|e| e.2).collect::<Vec<_>>();
+        loop {
+            let mut balance = vec![0; n];
+            for (&(u, v, _, _), &f) in edges.iter().zip(fs.iter()) {
+                balance[u] -= f;
+                balance[v] += f;
+            }
+            if (0..n).all(|v| v == s || v == t || balance[v] == 0) {
+                values.push(-balance[s]);
+                circulation |= balance[s] == 0;
+            }
+            let mut i = 0;
+            while i < m && fs[i] == edges[i].3 {
+                fs[i] = edges[i].2;
+                i += 1;
+            }
+            if i == m {
+                break;
+            }
+            fs[i] += 1;
+        }
+
+        let build = || {
+            let mut f = LowerBoundFlow::new(n);
+            for &(u, v, l, r) in &edges {
+                f.add_edge(u, v, l, r);
+            }
+            f
+        };
+        let check = |f: &LowerBoundFlow<i64>| {
+            let mut balance = vec![0; n];
+            for (id, &e) in edges.iter().enumerate() {
+                let (u, v, l, r, x) = f.get_edge(id);
+                assert_eq!((u, v, l, r), e);
+                assert!(l <= x && x <= r);
+                balance[u] -= x;
+                balance[v] += x;
+            }
+            assert!((0..n).all(|v| v == s || v == t || balance[v] == 0));
+            -balance[s]
+        };
+
+        let mut f = build();
+        assert_eq!(f.feasible(), circulation);
+        if circulation {
+            assert_eq!(check(&f), 0);
+        }
+        let mut f = build();
+        let res = f.max_flow(s, t);
+        assert_eq!(res, values.iter().cloned().max());
+        if let Some(x) = res {
+            assert_eq!(check(&f), x);
+        }
+        let mut f = build();
+        let res = f.min_flow(s, t);
+        assert_eq!(res, values.iter().cloned().min());
+        if let Some(x) = res {
+            assert_eq!(check(&f), x);
+        }
+    }
+}
+
+#[test]
+fn test_max_flow_algorithms() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..300 {
+        let n = rng.next_u32() as usize % 10 + 2;
+        let m = rng.next_u32() as usize % 40;
+        let mut flow: Flow<u64> = Flow::new(n);
+        for _ in 0..m {
+            let u = rng.next_u32() as usize % n;
+            let v = rng.next_u32() as usize % n;
+            flow.add_edge(u, v, rng.next_u64() % 1000);
+        }
+        let (s, t) = (0, n - 1);
+
+        let expected = flow.clone().max_flow_dinic(s, t);
+        for k in 0..3 {
+            let mut f = flow.clone();
+            let value = match k {
+                0 => f.max_flow_ff(s, t),
+                1 => f.max_flow_scaling(s, t),
+                _ => f.max_flow_push_relabel(s, t),
+            };
+            assert_eq!(value, expected);
+
+            // Edge flows form a valid flow of the value
+            let mut balance = vec![0i64; n + 1];
+            for id in 0..m {
+                let (u, v, cap, x) = f.get_edge(id);
+                assert!(x <= cap);
+                balance[u] -= x as i64;
+                balance[v] += x as i64;
+            }
+            for (v, &b) in balance.iter().enumerate() {
+                if v == s {
+                    assert_eq!(b, -(expected as i64));
+                } else if v == t {
+                    assert_eq!(b, expected as i64);
+                } else {
+                    assert_eq!(b, 0);
+                }
+            }
+        }
+    }
+
+    // Long augmenting path
+    let n = 100_000;
+    let mut flow: Flow<i64> = Flow::new(n);
+    for i in 0..n {
+        flow.add_edge(i, i + 1, 1 + i as i64 % 7);
+    }
+    assert_eq!(flow.clone().max_flow_dinic(0, n), 1);
+    assert_eq!(flow.clone().max_flow_ff(0, n), 1);
+    assert_eq!(flow.clone().max_flow_scaling(0, n), 1);
+    assert_eq!(flow.clone().max_flow_push_relabel(0, n), 1);
+
+    // Source and sink coincide
+    assert_eq!(flow.clone().max_flow_dinic(3, 3), 0);
+    assert_eq!(flow.clone().max_flow_ff(3, 3), 0);
+    assert_eq!(flow.clone().max_flow_scaling(3, 3), 0);
+    assert_eq!(flow.max_flow_push_relabel(3, 3), 0);
+}
+
 #[test]
 fn test_bipartite_matching() {
     use rand::{Rng, SeedableRng, StdRng};
@@ -234,5 +1174,397 @@ fn test_bipartite_matching() {
         }
     }
 
-    assert_eq!(bipartite_matching(&g), flow.max_flow_dinic(s, t));
+    let expected = flow.max_flow_dinic(s, t);
+    assert_eq!(bipartite_matching(&g), expected);
+
+    let mut hk = HopcroftKarp::new(size, size);
+    for (u, es) in g.iter().enumerate() {
+        for &v in es {
+            hk.add_edge(u, v - size);
+        }
+    }
+    let pairs = hk.solve();
+    assert_eq!(pairs.len(), expected);
+    let mut used_left = vec![false; size];
+    let mut used_right = vec![false; size];
+    for &(u, v) in &pairs {
+        assert!(g[u].contains(&(size + v)));
+        assert!(!used_left[u] && !used_right[v]);
+        used_left[u] = true;
+        used_right[v] = true;
+    }
+
+    let (cl, cr) = hk.min_vertex_cover();
+    assert_eq!(cl.len() + cr.len(), expected);
+    for (u, es) in g.iter().enumerate() {
+        for &v in es {
+            assert!(cl.contains(&u) || cr.contains(&(v - size)));
+        }
+    }
+    let (il, ir) = hk.max_independent_set();
+    assert_eq!(il.len() + ir.len(), 2 * size - expected);
+    for &u in &il {
+        assert!(g[u].iter().all(|&v| !ir.contains(&(v - size))));
+    }
+}
+
+#[test]
+fn test_dag_min_path_cover_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..200 {
+        let n = rng.next_u32() as usize % 7 + 1;
+        let mut g = vec![Vec::new(); n];
+        let mut edges = Vec::new();
+        for _ in 0..rng.next_u32() % 10 {
+            let u = rng.next_u32() as usize % n;
+            let v = rng.next_u32() as usize % n;
+            if u < v && !g[u].contains(&v) {
+                g[u].push(v);
+                edges.push((u, v));
+            }
+        }
+
+        // Largest set of edges with in-degree and out-degree at most 1
+        let m = edges.len();
+        let brute = (0..1usize << m)
+            .filter(|&mask| {
+                let mut indeg = vec![0; n];
+                let mut outdeg = vec![0; n];
+                for (i, &(u, v)) in edges.iter().enumerate() {
+                    if mask >> i & 1 == 1 {
+                        outdeg[u] += 1;
+                        indeg[v] += 1;
+                    }
+                }
+                indeg.iter().chain(outdeg.iter()).all(|&d| d <= 1)
+            })
+            .map(|mask| mask.count_ones() as usize)
+            .max()
+            .unwrap();
+
+        let paths = dag_min_path_cover(&g);
+        assert_eq!(paths.len(), n - brute);
+        let mut vs = paths.concat();
+        vs.sort();
+        assert_eq!(vs, (0..n).collect::<Vec<_>>());
+        for p in &paths {
+            for w in p.windows(2) {
+                assert!(g[w[0]].contains(&w[1]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn brute_min_cut(n: usize, edges: &[(usize, usize, usize)], s: usize, t: usize) -> usize {
+    (0..1usize << n)
+        .filter(|&m| m >> s & 1 == 1 && m >> t & 1 == 0)
+        .map(|m| {
+            edges
+                .iter()
+                .filter(|&&(u, v, _)| m >> u & 1 != m >> v & 1)
+                .map(|e| e.2)
+                .sum()
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn test_min_cut() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..100 {
+        let n = rng.next_u32() as usize % 7 + 2;
+        let edges = (0..rng.next_u32() as usize % 15)
+            .map(|_| {
+                (
+                    rng.next_u32() as usize % n,
+                    rng.next_u32() as usize % n,
+                    rng.next_u32() as usize % 10,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Directed s-t cut
+        let (s, t) = (0, n - 1);
+        let mut flow = Flow::new(n);
+        for &(u, v, c) in &edges {
+            flow.add_edge(u, v, c);
+        }
+        let f = flow.max_flow_ff(s, t);
+        let side = flow.cut(s);
+        let cut = flow.cut_edges(s);
+        assert_eq!(cut.iter().map(|e| e.2).sum::<usize>(), f);
+        let expected = edges
+            .iter()
+            .filter(|&&(u, v, _)| side.contains(&u) && !side.contains(&v))
+            .count();
+        assert_eq!(cut.len(), expected);
+        let brute = (0..1usize << n)
+            .filter(|&m| m >> s & 1 == 1 && m >> t & 1 == 0)
+            .map(|m| {
+                edges
+                    .iter()
+                    .filter(|&&(u, v, _)| m >> u & 1 == 1 && m >> v & 1 == 0)
+                    .map(|e| e.2)
+                    .sum::<usize>()
+            })
+            .min()
+            .unwrap();
+        assert_eq!(f, brute);
+
+        // Undirected
+        let g = Graph::undirected(
+            n,
+            &edges
+                .iter()
+                .map(|&(u, v, c)| (u, v, c as u64))
+                .collect::<Vec<_>>(),
+        );
+        let (w, part) = stoer_wagner(&g).unwrap();
+        let global = (1..n)
+            .map(|t| brute_min_cut(n, &edges, 0, t))
+            .min()
+            .unwrap();
+        assert_eq!(w, global as u64);
+        assert!(!part.is_empty() && part.len() < n);
+        let crossing = edges
+            .iter()
+            .filter(|&&(u, v, _)| part.contains(&u) != part.contains(&v))
+            .map(|e| e.2 as u64)
+            .sum::<u64>();
+        assert_eq!(crossing, w);
+
+        let gh = GomoryHu::new(n, &edges);
+        let gh64 = GomoryHu::new(
+            n,
+            &edges
+                .iter()
+                .map(|&(u, v, c)| (u, v, c as i64))
+                .collect::<Vec<_>>(),
+        );
+        for u in 0..n {
+            for v in 0..n {
+                if u != v {
+                    assert_eq!(gh.min_cut(u, v), brute_min_cut(n, &edges, u, v));
+                    assert_eq!(gh64.min_cut(u, v), gh.min_cut(u, v) as i64);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_min_cost_flow_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..200 {
+        let n = rng.next_u32() as usize % 4 + 2;
+        let m = rng.next_u32() as usize % 6 + 1;
+        let edges = (0..m)
+            .map(|_| {
+                let u = rng.next_u32() as usize % n;
+                let v = (u + 1 + rng.next_u32() as usize % (n - 1)) % n;
+                // Backward edges are expensive enough that there is no negative cycle
+                let cost = if u < v {
+                    rng.next_u32() as i64 % 11 - 5
+                } else {
+                    5 * (u - v) as i64 + rng.next_u32() as i64 % 6
+                };
+                (u, v, rng.next_u32() as i64 % 3, cost)
+            })
+            .collect::<Vec<_>>();
+        let (s, t) = (0, n - 1);
+
+        // Minimum cost of each flow value by enumerating flows on every edge
+        let mut best = std::collections::BTreeMap::new();
+        let mut fs = vec![0; m];
+        loop {
+            let mut balance = vec![0; n];
+            for (&(u, v, _, _), &f) in edges.iter().zip(fs.iter()) {
+                balance[u] -= f;
+                balance[v] += f;
+            }
+            if (0..n).all(|v| v == s || v == t || balance[v] == 0) && balance[t] >= 0 {
+                let cost = edges
+                    .iter()
+                    .zip(fs.iter())
+                    .map(|(e, &f)| e.3 * f)
+                    .sum::<i64>();
+                let e = best.entry(balance[t]).or_insert(cost);
+                *e = cost.min(*e);
+            }
+            let mut i = 0;
+            while i < m && fs[i] == edges[i].2 {
+                fs[i] = 0;
+                i += 1;
+            }
+            if i == m {
+                break;
+            }
+            fs[i] += 1;
+        }
+
+        let mut mcf = MinCostFlow::new(n);
+        for &(u, v, c, w) in &edges {
+            mcf.add_edge(u, v, c, w);
+        }
+        let slope = mcf.slope(s, t, i64::MAX);
+        let &(max_flow, _) = slope.last().unwrap();
+        assert_eq!(max_flow, *best.keys().last().unwrap());
+        for w in slope.windows(2) {
+            let ((f0, c0), (f1, c1)) = (w[0], w[1]);
+            for f in f0..=f1 {
+                assert_eq!(c0 + (c1 - c0) / (f1 - f0) * (f - f0), best[&f]);
+            }
+        }
+
+        let total = (0..m).map(|id| {
+            let (u, v, cap, flow, cost) = mcf.get_edge(id);
+            assert_eq!((u, v, cap, cost), edges[id]);
+            assert!(0 <= flow && flow <= cap);
+            flow * cost
+        });
+        assert_eq!(total.sum::<i64>(), slope.last().unwrap().1);
+
+        let mut mcf = MinCostFlow::new(n);
+        for &(u, v, c, w) in &edges {
+            mcf.add_edge(u, v, c, w);
+        }
+        let limit = rng.next_u32() as i64 % 3;
+        let (f, c) = mcf.flow(s, t, limit);
+        assert_eq!(f, limit.min(max_flow));
+        assert_eq!(c, best[&f]);
+    }
+}
+
+#[test]
+fn test_min_cost_flow_assignment() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+    let n = 6;
+    let cost = (0..n)
+        .map(|_| {
+            (0..n)
+                .map(|_| rng.next_u32() as i64 % 100)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut mcf = MinCostFlow::new(2 * n + 2);
+    let (s, t) = (2 * n, 2 * n + 1);
+    for (i, row) in cost.iter().enumerate() {
+        mcf.add_edge(s, i, 1, 0);
+        mcf.add_edge(n + i, t, 1, 0);
+        for (j, &c) in row.iter().enumerate() {
+            mcf.add_edge(i, n + j, 1, c);
+        }
+    }
+
+    let mut perm = (0..n).collect::<Vec<_>>();
+    let mut brute = i64::MAX;
+    loop {
+        brute = brute.min((0..n).map(|i| cost[i][perm[i]]).sum());
+        // next permutation
+        let i = match (1..n).rev().find(|&i| perm[i - 1] < perm[i]) {
+            Some(i) => i,
+            None => break,
+        };
+        let j = (i..n).rev().find(|&j| perm[i - 1] < perm[j]).unwrap();
+        perm.swap(i - 1, j);
+        perm[i..].reverse();
+    }
+
+    let mut mcf32: MinCostFlow<i32> = MinCostFlow::new(2 * n + 2);
+    for (i, row) in cost.iter().enumerate() {
+        mcf32.add_edge(s, i, 1, 0);
+        mcf32.add_edge(n + i, t, 1, 0);
+        for (j, &c) in row.iter().enumerate() {
+            mcf32.add_edge(i, n + j, 1, c as i32);
+        }
+    }
+
+    assert_eq!(mcf.flow(s, t, n as i64), (n as i64, brute));
+    assert_eq!(mcf32.flow(s, t, n as i32), (n as i32, brute as i32));
+}
+
+#[cfg(test)]
+use test::Bencher;
+
+#[cfg(test)]
+fn random_flow(n: usize, m: usize) -> Flow<u64> {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+    let mut flow = Flow::new(n);
+    for _ in 0..m {
+        let u = rng.next_u32() as usize % n;
+        let v = rng.next_u32() as usize % n;
+        flow.add_edge(u, v, rng.next_u64() % 1_000_000);
+    }
+    flow
+}
+
+#[cfg(test)]
+/// Layers fully connected to the next one, with a long path of small capacities
+/// from each layer to the sink. Dinic's algorithm needs many phases on this.
+fn layered_flow(layers: usize, width: usize) -> Flow<u64> {
+    let n = layers * width + 2;
+    let (s, t) = (n - 2, n - 1);
+    let mut flow = Flow::new(n);
+    for i in 0..width {
+        flow.add_edge(s, i, 1_000_000);
+    }
+    for l in 0..layers - 1 {
+        for i in 0..width {
+            for j in 0..width {
+                flow.add_edge(l * width + i, (l + 1) * width + j, 1000 + (i * j) as u64);
+            }
+            flow.add_edge(l * width + i, t, (l * width + i) as u64 % 3 + 1);
+        }
+    }
+    for i in 0..width {
+        flow.add_edge((layers - 1) * width + i, t, 1);
+    }
+    flow
+}
+
+#[bench]
+fn bench_flow_dinic_random(b: &mut Bencher) {
+    let flow = random_flow(1000, 20000);
+    b.iter(|| flow.clone().max_flow_dinic(0, 999));
+}
+
+#[bench]
+fn bench_flow_scaling_random(b: &mut Bencher) {
+    let flow = random_flow(1000, 20000);
+    b.iter(|| flow.clone().max_flow_scaling(0, 999));
+}
+
+#[bench]
+fn bench_flow_push_relabel_random(b: &mut Bencher) {
+    let flow = random_flow(1000, 20000);
+    b.iter(|| flow.clone().max_flow_push_relabel(0, 999));
+}
+
+#[bench]
+fn bench_flow_dinic_layered(b: &mut Bencher) {
+    let flow = layered_flow(50, 20);
+    b.iter(|| flow.clone().max_flow_dinic(1000, 1001));
+}
+
+#[bench]
+fn bench_flow_scaling_layered(b: &mut Bencher) {
+    let flow = layered_flow(50, 20);
+    b.iter(|| flow.clone().max_flow_scaling(1000, 1001));
+}
+
+#[bench]
+fn bench_flow_push_relabel_layered(b: &mut Bencher) {
+    let flow = layered_flow(50, 20);
+    b.iter(|| flow.clone().max_flow_push_relabel(1000, 1001));
 }
@@ -0,0 +1,233 @@
+use crate::monoid::Monoid;
+use cargo_snippet::snippet;
+
+#[snippet("Doubling")]
+#[allow(dead_code)]
+/// Doubling on a functional graph v -> next[v].
+/// O(N log K) construction, O(log K) query
+pub struct Doubling {
+    /// table[i][v] is the vertex reached from v after 2^i steps
+    table: Vec<Vec<usize>>,
+}
+
+#[snippet("Doubling")]
+#[allow(dead_code)]
+impl Doubling {
+    /// Supports `kth(v, k)` for k <= max_k
+    pub fn new(next: &[usize], max_k: u64) -> Doubling {
+        let mut table = vec![next.to_vec()];
+        while table.len() < 64 && 1u64 << table.len() <= max_k {
+            let prev = table.last().unwrap();
+            let row = prev.iter().map(|&u| prev[u]).collect();
+            table.push(row);
+        }
+        Doubling { table }
+    }
+
+    /// Vertex reached from `v` after `k` steps
+    pub fn kth(&self, mut v: usize, k: u64) -> usize {
+        assert!(
+            self.table.len() == 64 || k >> self.table.len() == 0,
+            "k is too large"
+        );
+        for (i, row) in self.table.iter().enumerate() {
+            if k >> i & 1 == 1 {
+                v = row[v];
+            }
+        }
+        v
+    }
+}
+
+#[snippet("DoublingFold")]
+#[snippet(include = "Monoid")]
+#[allow(dead_code)]
+/// Doubling on a functional graph with a monoid value on each edge v -> next[v].
+pub struct DoublingFold<M: Monoid> {
+    table: Vec<Vec<(usize, M::T)>>,
+}
+
+#[snippet("DoublingFold")]
+#[allow(dead_code)]
+impl<M: Monoid> DoublingFold<M> {
+    /// `value[v]` is the value of the edge v -> next[v]. Supports k <= max_k
+    pub fn new(next: &[usize], value: &[M::T], max_k: u64) -> DoublingFold<M> {
+        let mut table = vec![next
+            .iter()
+            .cloned()
+            .zip(value.iter().cloned())
+            .collect::<Vec<_>>()];
+        while table.len() < 64 && 1u64 << table.len() <= max_k {
+            let prev = table.last().unwrap();
+            let row = prev
+                .iter()
+                .map(|&(u, ref x)| (prev[u].0, M::op(x, &prev[u].1)))
+                .collect();
+            table.push(row);
+        }
+        DoublingFold { table }
+    }
+
+    /// (vertex reached from `v` after `k` steps, fold of the values of the edges on the walk in order)
+    pub fn kth(&self, mut v: usize, k: u64) -> (usize, M::T) {
+        assert!(
+            self.table.len() == 64 || k >> self.table.len() == 0,
+            "k is too large"
+        );
+        let mut acc = M::id();
+        for (i, row) in self.table.iter().enumerate() {
+            if k >> i & 1 == 1 {
+                acc = M::op(&acc, &row[v].1);
+                v = row[v].0;
+            }
+        }
+        (v, acc)
+    }
+}
+
+#[snippet("FunctionalGraph")]
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+/// Cycle structure of a functional graph v -> next[v]. O(N) without recursion
+pub struct FunctionalGraph {
+    /// Number of steps from v until it enters a cycle. 0 if v is on a cycle
+    pub tail: Vec<usize>,
+    /// First vertex on a cycle reached from v
+    pub entry: Vec<usize>,
+    /// Id of the cycle reached from v
+    pub cycle_id: Vec<usize>,
+    /// Vertices of each cycle in the order of the walk
+    pub cycles: Vec<Vec<usize>>,
+}
+
+#[snippet("FunctionalGraph")]
+#[allow(dead_code)]
+impl FunctionalGraph {
+    pub fn new(next: &[usize]) -> FunctionalGraph {
+        let n = next.len();
+        let mut tail = vec![0; n];
+        let mut entry = vec![0; n];
+        let mut cycle_id = vec![0; n];
+        let mut cycles: Vec<Vec<usize>> = Vec::new();
+        // 0: unvisited, 1: on the current walk, 2: done
+        let mut state = vec![0u8; n];
+        let mut walk = Vec::new();
+
+        for s in 0..n {
+            if state[s] != 0 {
+                continue;
+            }
+            walk.clear();
+            let mut v = s;
+            while state[v] == 0 {
+                state[v] = 1;
+                walk.push(v);
+                v = next[v];
+            }
+
+            if state[v] == 1 {
+                let i = walk.iter().position(|&u| u == v).unwrap();
+                let cycle = walk.split_off(i);
+                for &u in &cycle {
+                    state[u] = 2;
+                    entry[u] = u;
+                    cycle_id[u] = cycles.len();
+                }
+                cycles.push(cycle);
+            }
+
+            for &u in walk.iter().rev() {
+                let t = next[u];
+                state[u] = 2;
+                tail[u] = tail[t] + 1;
+                entry[u] = entry[t];
+                cycle_id[u] = cycle_id[t];
+            }
+        }
+
+        FunctionalGraph {
+            tail,
+            entry,
+            cycle_id,
+            cycles,
+        }
+    }
+
+    pub fn cycle_len(&self, v: usize) -> usize {
+        self.cycles[self.cycle_id[v]].len()
+    }
+}
+
+#[test]
+fn test_functional_graph() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    // Composition of affine maps x -> a * x + b mod P, applied left to right
+    enum Affine {}
+    impl Monoid for Affine {
+        type T = (u64, u64);
+        fn id() -> Self::T {
+            (1, 0)
+        }
+        fn op(f: &Self::T, g: &Self::T) -> Self::T {
+            const P: u64 = 1_000_000_007;
+            (f.0 * g.0 % P, (f.1 * g.0 + g.1) % P)
+        }
+    }
+
+    for _ in 0..100 {
+        let n = rng.next_u32() as usize % 30 + 1;
+        let next = (0..n)
+            .map(|_| rng.next_u32() as usize % n)
+            .collect::<Vec<_>>();
+        let value = (0..n)
+            .map(|_| (rng.next_u64() % 100, rng.next_u64() % 100))
+            .collect::<Vec<_>>();
+        let doubling = Doubling::new(&next, 1 << 61);
+        let fold: DoublingFold<Affine> = DoublingFold::new(&next, &value, 100);
+        let fg = FunctionalGraph::new(&next);
+
+        for s in 0..n {
+            let mut v = s;
+            let mut acc = Affine::id();
+            let mut seen = vec![None; n];
+            for k in 0..=100 {
+                assert_eq!(doubling.kth(s, k as u64), v);
+                assert_eq!(fold.kth(s, k as u64), (v, acc));
+                if seen[v].is_none() {
+                    seen[v] = Some(k);
+                }
+                acc = Affine::op(&acc, &value[v]);
+                v = next[v];
+            }
+
+            // The first repeated vertex is the entry of the cycle
+            let mut v = s;
+            let mut steps = 0;
+            let mut visited = vec![false; n];
+            while !visited[v] {
+                visited[v] = true;
+                v = next[v];
+                steps += 1;
+            }
+            let len = steps - seen[v].unwrap();
+            assert_eq!(fg.entry[s], v);
+            assert_eq!(fg.tail[s], seen[v].unwrap());
+            assert_eq!(fg.cycle_len(s), len);
+            assert_eq!(fg.cycle_id[s], fg.cycle_id[v]);
+
+            let big = (1u64 << 60) + rng.next_u64() % 1000;
+            let t = fg.tail[s] as u64;
+            let expected = doubling.kth(fg.entry[s], (big - t) % len as u64);
+            assert_eq!(doubling.kth(s, big), expected);
+        }
+
+        for (i, cycle) in fg.cycles.iter().enumerate() {
+            for (j, &v) in cycle.iter().enumerate() {
+                assert_eq!(fg.cycle_id[v], i);
+                assert_eq!(next[v], cycle[(j + 1) % cycle.len()]);
+            }
+        }
+    }
+}
@@ -1,58 +1,528 @@
+use crate::monoid::Monoid;
 use cargo_snippet::snippet;
-#[snippet("strongly_connected_component")]
-#[allow(dead_code)]
-pub fn strongly_connected_component(g: &[Vec<usize>]) -> Vec<usize> {
-    fn dfs(v: usize, g: &[Vec<usize>], vs: &mut Vec<usize>, used: &mut [bool]) {
-        used[v] = true;
-        for &to in &g[v] {
-            if !used[to] {
-                dfs(to, g, vs, used);
+
+#[snippet("Adjacency")]
+/// Read-only view of a graph as an adjacency list.
+/// Implemented for `[Vec<usize>]`, `Vec<Vec<usize>>`, `Graph<W>` and `Tree`.
+pub trait Adjacency {
+    fn vertices(&self) -> usize;
+    fn degree(&self, v: usize) -> usize;
+    /// `i`-th neighbor of `v`
+    fn neighbor(&self, v: usize, i: usize) -> usize;
+}
+
+#[snippet("Adjacency")]
+impl Adjacency for [Vec<usize>] {
+    fn vertices(&self) -> usize {
+        self.len()
+    }
+    fn degree(&self, v: usize) -> usize {
+        self[v].len()
+    }
+    fn neighbor(&self, v: usize, i: usize) -> usize {
+        self[v][i]
+    }
+}
+
+#[snippet("Adjacency")]
+impl Adjacency for Vec<Vec<usize>> {
+    fn vertices(&self) -> usize {
+        self.len()
+    }
+    fn degree(&self, v: usize) -> usize {
+        self[v].len()
+    }
+    fn neighbor(&self, v: usize, i: usize) -> usize {
+        self[v][i]
+    }
+}
+
+#[snippet("Graph")]
+#[snippet(include = "Adjacency")]
+#[derive(Clone, Debug)]
+/// Graph in compressed sparse row form.
+/// Every edge keeps the id it had in the input edge list.
+/// An undirected edge appears in the adjacency of both endpoints with the same id.
+pub struct Graph<W> {
+    pub directed: bool,
+    /// from, to, weight
+    pub edges: Vec<(usize, usize, W)>,
+    start: Vec<usize>,
+    /// to, edge id
+    adj: Vec<(usize, usize)>,
+}
+
+#[snippet("Graph")]
+impl<W: Clone> Graph<W> {
+    #[allow(dead_code)]
+    pub fn new(n: usize, edges: Vec<(usize, usize, W)>, directed: bool) -> Graph<W> {
+        let mut start = vec![0; n + 1];
+        for &(u, v, _) in &edges {
+            start[u + 1] += 1;
+            if !directed {
+                start[v + 1] += 1;
+            }
+        }
+        for i in 0..n {
+            start[i + 1] += start[i];
+        }
+
+        let mut pos = start.clone();
+        let mut adj = vec![(0, 0); start[n]];
+        for (id, &(u, v, _)) in edges.iter().enumerate() {
+            adj[pos[u]] = (v, id);
+            pos[u] += 1;
+            if !directed {
+                adj[pos[v]] = (u, id);
+                pos[v] += 1;
+            }
+        }
+
+        Graph {
+            directed,
+            edges,
+            start,
+            adj,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn directed(n: usize, edges: &[(usize, usize, W)]) -> Graph<W> {
+        Graph::new(n, edges.to_vec(), true)
+    }
+
+    #[allow(dead_code)]
+    pub fn undirected(n: usize, edges: &[(usize, usize, W)]) -> Graph<W> {
+        Graph::new(n, edges.to_vec(), false)
+    }
+
+    #[allow(dead_code)]
+    /// For 1-indexed edges as read by `get!(usize, usize, W; m)`
+    pub fn from_1indexed(n: usize, edges: &[(usize, usize, W)], directed: bool) -> Graph<W> {
+        Graph::new(
+            n,
+            edges
+                .iter()
+                .map(|&(u, v, ref w)| (u - 1, v - 1, w.clone()))
+                .collect(),
+            directed,
+        )
+    }
+
+    #[allow(dead_code)]
+    /// Graph with every edge reversed. Edge ids are kept.
+    pub fn reversed(&self) -> Graph<W> {
+        Graph::new(
+            self.len(),
+            self.edges
+                .iter()
+                .map(|&(u, v, ref w)| (v, u, w.clone()))
+                .collect(),
+            self.directed,
+        )
+    }
+
+    #[allow(dead_code)]
+    /// Returns (number of components, component id of each vertex).
+    /// Edges are followed in both directions.
+    pub fn connected_components(&self) -> (usize, Vec<usize>) {
+        let n = self.len();
+        let rev = if self.directed {
+            Some(self.reversed())
+        } else {
+            None
+        };
+        let mut cmp = vec![!0; n];
+        let mut k = 0;
+        for s in 0..n {
+            if cmp[s] != !0 {
+                continue;
+            }
+            cmp[s] = k;
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                let back = rev.as_ref().map(|r| r.adj(v)).unwrap_or(&[]);
+                for &(to, _) in self.adj(v).iter().chain(back) {
+                    if cmp[to] == !0 {
+                        cmp[to] = k;
+                        stack.push(to);
+                    }
+                }
+            }
+            k += 1;
+        }
+        (k, cmp)
+    }
+
+    #[allow(dead_code)]
+    /// 2-coloring of an undirected graph. `None` if the graph is not bipartite.
+    pub fn bipartite_coloring(&self) -> Option<Vec<bool>> {
+        let n = self.len();
+        let mut color = vec![None; n];
+        for s in 0..n {
+            if color[s].is_some() {
+                continue;
+            }
+            color[s] = Some(false);
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                let c = color[v].unwrap();
+                for &(to, _) in self.adj(v) {
+                    match color[to] {
+                        None => {
+                            color[to] = Some(!c);
+                            stack.push(to);
+                        }
+                        Some(d) if d == c => return None,
+                        _ => {}
+                    }
+                }
             }
         }
-        vs.push(v);
+        Some(color.into_iter().map(|c| c.unwrap()).collect())
     }
-    fn rdfs(v: usize, k: usize, g_rev: &[Vec<usize>], cmp: &mut [usize], used: &mut [bool]) {
-        used[v] = true;
-        cmp[v] = k;
+}
+
+#[snippet("Graph")]
+impl<W> Graph<W> {
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.start.len() - 1
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[allow(dead_code)]
+    /// (to, edge id) of edges leaving `v`
+    pub fn adj(&self, v: usize) -> &[(usize, usize)] {
+        &self.adj[self.start[v]..self.start[v + 1]]
+    }
+
+    #[allow(dead_code)]
+    pub fn weight(&self, id: usize) -> &W {
+        &self.edges[id].2
+    }
+
+    #[allow(dead_code)]
+    /// The endpoint of edge `id` which is not `v`
+    pub fn opposite(&self, id: usize, v: usize) -> usize {
+        let (a, b, _) = self.edges[id];
+        a ^ b ^ v
+    }
+
+    #[allow(dead_code)]
+    /// Id of an edge going back from the head of edge `id` to its tail. O(degree)
+    /// For an undirected graph or a self-loop this may be `id` itself.
+    pub fn reverse_edge(&self, id: usize) -> Option<usize> {
+        if !self.directed {
+            return Some(id);
+        }
+        let (u, v, _) = self.edges[id];
+        self.adj(v)
+            .iter()
+            .find(|&&(to, _)| to == u)
+            .map(|&(_, e)| e)
+    }
+}
+
+#[snippet("Graph")]
+impl Graph<()> {
+    #[allow(dead_code)]
+    /// Undirected graph from an adjacency list. An edge u-v may appear in g[u], g[v] or both.
+    /// Parallel edges are kept as the larger of the counts of the two directions.
+    /// Self-loops are dropped.
+    pub fn from_adjacency<G: Adjacency + ?Sized>(g: &G) -> Graph<()> {
+        // (smaller endpoint, larger endpoint, whether it was listed at the smaller one)
+        let mut pairs = Vec::new();
+        for u in 0..g.vertices() {
+            for i in 0..g.degree(u) {
+                let v = g.neighbor(u, i);
+                if u != v {
+                    pairs.push((u.min(v), u.max(v), u < v));
+                }
+            }
+        }
+        pairs.sort();
 
-        for &to in &g_rev[v] {
-            if !used[to] {
-                rdfs(to, k, g_rev, cmp, used);
+        let mut edges = Vec::new();
+        let mut i = 0;
+        while i < pairs.len() {
+            let (u, v, _) = pairs[i];
+            let j = i + pairs[i..]
+                .iter()
+                .take_while(|p| (p.0, p.1) == (u, v))
+                .count();
+            let forward = pairs[i..j].iter().filter(|p| p.2).count();
+            for _ in 0..forward.max(j - i - forward) {
+                edges.push((u, v, ()));
             }
+            i = j;
         }
+        Graph::new(g.vertices(), edges, false)
+    }
+
+    #[allow(dead_code)]
+    pub fn unweighted(n: usize, edges: &[(usize, usize)], directed: bool) -> Graph<()> {
+        Graph::new(
+            n,
+            edges.iter().map(|&(u, v)| (u, v, ())).collect(),
+            directed,
+        )
+    }
+
+    #[allow(dead_code)]
+    /// For 1-indexed edges as read by `get!(usize, usize; m)`
+    pub fn unweighted_1indexed(n: usize, edges: &[(usize, usize)], directed: bool) -> Graph<()> {
+        Graph::new(
+            n,
+            edges.iter().map(|&(u, v)| (u - 1, v - 1, ())).collect(),
+            directed,
+        )
     }
+}
+
+#[snippet("Graph")]
+impl<W> Adjacency for Graph<W> {
+    fn vertices(&self) -> usize {
+        self.start.len() - 1
+    }
+    fn degree(&self, v: usize) -> usize {
+        self.start[v + 1] - self.start[v]
+    }
+    fn neighbor(&self, v: usize, i: usize) -> usize {
+        self.adj[self.start[v] + i].0
+    }
+}
 
+#[snippet("strongly_connected_component")]
+#[snippet(include = "Adjacency")]
+#[allow(dead_code)]
+/// Kosaraju's algorithm without recursion.
+/// Component ids are in topological order of the condensation.
+pub fn strongly_connected_component<G: Adjacency + ?Sized>(g: &G) -> Vec<usize> {
+    let n = g.vertices();
     let g_rev = {
-        let mut g_rev = vec![Vec::new(); g.len()];
-        for (i, es) in g.iter().enumerate() {
-            for &to in es {
-                g_rev[to].push(i);
+        let mut g_rev = vec![Vec::new(); n];
+        for v in 0..n {
+            for i in 0..g.degree(v) {
+                g_rev[g.neighbor(v, i)].push(v);
             }
         }
         g_rev
     };
 
-    let mut vs = Vec::new();
-    let mut used = vec![false; g.len()];
-    for v in 0..g.len() {
-        if !used[v] {
-            dfs(v, g, &mut vs, &mut used);
+    let mut vs = Vec::with_capacity(n);
+    let mut used = vec![false; n];
+    for s in 0..n {
+        if used[s] {
+            continue;
+        }
+        used[s] = true;
+        let mut stack = vec![(s, 0)];
+        while let Some(&mut (v, ref mut i)) = stack.last_mut() {
+            if *i < g.degree(v) {
+                let to = g.neighbor(v, *i);
+                *i += 1;
+                if !used[to] {
+                    used[to] = true;
+                    stack.push((to, 0));
+                }
+            } else {
+                vs.push(v);
+                stack.pop();
+            }
         }
     }
-    let mut used = vec![false; g.len()];
-    let mut cmp = vec![0; g.len()];
+
+    let mut cmp = vec![!0; n];
     let mut k = 0;
-    for &v in vs.iter().rev() {
-        if !used[v] {
-            rdfs(v, k, &g_rev, &mut cmp, &mut used);
-            k += 1;
+    for &s in vs.iter().rev() {
+        if cmp[s] != !0 {
+            continue;
+        }
+        cmp[s] = k;
+        let mut stack = vec![s];
+        while let Some(v) = stack.pop() {
+            for &to in &g_rev[v] {
+                if cmp[to] == !0 {
+                    cmp[to] = k;
+                    stack.push(to);
+                }
+            }
         }
+        k += 1;
     }
 
     cmp
 }
 
+#[snippet("SCC")]
+#[snippet(include = "strongly_connected_component")]
+#[derive(Clone, Debug)]
+/// Strongly connected components and their condensation
+pub struct SCC {
+    pub count: usize,
+    /// Component id of each vertex. Ids are in topological order
+    pub cmp: Vec<usize>,
+    pub members: Vec<Vec<usize>>,
+    /// Deduplicated edges between components
+    pub dag: Vec<Vec<usize>>,
+}
+
+#[snippet("SCC")]
+impl SCC {
+    #[allow(dead_code)]
+    pub fn new<G: Adjacency + ?Sized>(g: &G) -> SCC {
+        let cmp = strongly_connected_component(g);
+        let count = cmp.iter().map(|&c| c + 1).max().unwrap_or(0);
+
+        let mut members = vec![Vec::new(); count];
+        for (v, &c) in cmp.iter().enumerate() {
+            members[c].push(v);
+        }
+
+        let mut dag = vec![Vec::new(); count];
+        for v in 0..g.vertices() {
+            for i in 0..g.degree(v) {
+                let to = g.neighbor(v, i);
+                if cmp[v] != cmp[to] {
+                    dag[cmp[v]].push(cmp[to]);
+                }
+            }
+        }
+        for es in &mut dag {
+            es.sort();
+            es.dedup();
+        }
+
+        SCC {
+            count,
+            cmp,
+            members,
+            dag,
+        }
+    }
+}
+
+#[snippet("DominatorTree")]
+#[snippet(include = "Adjacency")]
+#[snippet(include = "Tree")]
+#[derive(Clone, Debug)]
+/// Dominator tree of a directed graph by the Lengauer-Tarjan algorithm without recursion.
+/// O(E log V)
+pub struct DominatorTree {
+    pub root: usize,
+    /// Immediate dominator. `None` for the root and vertices unreachable from the root
+    pub idom: Vec<Option<usize>>,
+}
+
+#[snippet("DominatorTree")]
+impl DominatorTree {
+    #[allow(dead_code)]
+    pub fn new<G: Adjacency + ?Sized>(root: usize, g: &G) -> DominatorTree {
+        fn eval(v: usize, anc: &mut [usize], label: &mut [usize], semi: &[usize]) -> usize {
+            if anc[v] == !0 {
+                return v;
+            }
+            let mut path = Vec::new();
+            let mut x = v;
+            while anc[anc[x]] != !0 {
+                path.push(x);
+                x = anc[x];
+            }
+            for &x in path.iter().rev() {
+                let a = anc[x];
+                if semi[label[a]] < semi[label[x]] {
+                    label[x] = label[a];
+                }
+                anc[x] = anc[a];
+            }
+            label[v]
+        }
+
+        let n = g.vertices();
+        let mut rg = vec![Vec::new(); n];
+        for v in 0..n {
+            for i in 0..g.degree(v) {
+                rg[g.neighbor(v, i)].push(v);
+            }
+        }
+
+        // Vertices are renumbered in DFS pre-order
+        let mut idx = vec![!0; n];
+        let mut order = vec![root];
+        let mut par = vec![!0];
+        idx[root] = 0;
+        let mut stack = vec![(root, 0)];
+        while let Some(&mut (v, ref mut i)) = stack.last_mut() {
+            if *i < g.degree(v) {
+                let to = g.neighbor(v, *i);
+                *i += 1;
+                if idx[to] == !0 {
+                    idx[to] = order.len();
+                    order.push(to);
+                    par.push(idx[v]);
+                    stack.push((to, 0));
+                }
+            } else {
+                stack.pop();
+            }
+        }
+
+        let k = order.len();
+        let mut semi = (0..k).collect::<Vec<_>>();
+        let mut label = (0..k).collect::<Vec<_>>();
+        let mut anc = vec![!0; k];
+        let mut idom = vec![0; k];
+        let mut bucket = vec![Vec::new(); k];
+        for w in (1..k).rev() {
+            for &v in &rg[order[w]] {
+                if idx[v] != !0 {
+                    let u = eval(idx[v], &mut anc, &mut label, &semi);
+                    semi[w] = semi[w].min(semi[u]);
+                }
+            }
+            bucket[semi[w]].push(w);
+            let p = par[w];
+            anc[w] = p;
+            for v in std::mem::take(&mut bucket[p]) {
+                let u = eval(v, &mut anc, &mut label, &semi);
+                idom[v] = if semi[u] < semi[v] { u } else { p };
+            }
+        }
+        for w in 1..k {
+            if idom[w] != semi[w] {
+                idom[w] = idom[idom[w]];
+            }
+        }
+
+        let mut res = vec![None; n];
+        for w in 1..k {
+            res[order[w]] = Some(order[idom[w]]);
+        }
+        DominatorTree { root, idom: res }
+    }
+
+    #[allow(dead_code)]
+    /// Vertices unreachable from the root are left isolated and do not appear in the orders.
+    /// They are neither ancestors nor descendants of any vertex in `is_ancestor`.
+    pub fn to_tree(&self) -> Tree {
+        let n = self.idom.len();
+        let mut childs = vec![Vec::new(); n];
+        for (v, p) in self.idom.iter().enumerate() {
+            if let Some(p) = *p {
+                childs[p].push(v);
+            }
+        }
+        Tree::build(self.root, self.idom.clone(), childs)
+    }
+}
+
 #[snippet("LCA")]
+#[snippet(include = "Adjacency")]
 #[allow(dead_code)]
 pub struct LCA {
     pub depth: Vec<usize>,
@@ -62,34 +532,28 @@ pub struct LCA {
 #[snippet("LCA")]
 #[allow(dead_code)]
 impl LCA {
-    pub fn new(g: &[Vec<usize>]) -> LCA {
+    pub fn new<G: Adjacency + ?Sized>(g: &G) -> LCA {
         LCA::with_root(0, g)
     }
 
-    pub fn with_root(root: usize, g: &[Vec<usize>]) -> LCA {
-        fn dfs(
-            i: usize,
-            p: Option<usize>,
-            d: usize,
-            g: &[Vec<usize>],
-            depth: &mut [usize],
-            parent: &mut [Vec<Option<usize>>],
-        ) {
+    pub fn with_root<G: Adjacency + ?Sized>(root: usize, g: &G) -> LCA {
+        let n = g.vertices();
+        let l2 = (1..).find(|i| 1usize << i > n).unwrap();
+        let mut depth = vec![0; n];
+        let mut parent = vec![vec![None; l2 + 1]; n];
+
+        let mut stack = vec![(root, None, 0)];
+        while let Some((i, p, d)) = stack.pop() {
             parent[i][0] = p;
             depth[i] = d;
 
-            for &t in &g[i] {
+            for k in 0..g.degree(i) {
+                let t = g.neighbor(i, k);
                 if Some(t) != p {
-                    dfs(t, Some(i), d + 1, g, depth, parent);
+                    stack.push((t, Some(i), d + 1));
                 }
             }
         }
-        let n = g.len();
-        let l2 = (1..).find(|i| 1usize << i > n).unwrap();
-        let mut depth = vec![0; n];
-        let mut parent = vec![vec![None; l2 + 1]; n];
-
-        dfs(root, None, 0, &g, &mut depth, &mut parent);
 
         for i in 1..l2 + 1 {
             for j in 0..n {
@@ -124,223 +588,1121 @@ impl LCA {
         }
         self.parent[a][0].unwrap()
     }
-}
-
-#[snippet("Tree")]
-#[snippet("HeavyLightDecomposition")]
-pub struct Tree {
-    pub root: usize,
-    pub parent: Vec<Option<usize>>,
-    pub childs: Vec<Vec<usize>>,
-}
-
-#[snippet("Tree")]
-#[snippet("HeavyLightDecomposition")]
-impl Tree {
-    pub fn from_neighbor_list(n: usize, root: usize, g: &[Vec<usize>]) -> Tree {
-        let mut parent = vec![None; n];
-        let mut childs = vec![Vec::new(); n];
 
-        let mut stack = vec![(root, None)];
+    /// Number of edges between `a` and `b`
+    pub fn dist(&self, a: usize, b: usize) -> usize {
+        self.depth[a] + self.depth[b] - 2 * self.depth[self.lca(a, b)]
+    }
 
-        while let Some((i, p)) = stack.pop() {
-            parent[i] = p;
+    /// `wdepth` is the weighted depth of each vertex. See `weighted_depth`
+    pub fn weighted_dist<T>(&self, a: usize, b: usize, wdepth: &[T]) -> T
+    where
+        T: Copy + std::ops::Add<Output = T> + std::ops::Sub<Output = T>,
+    {
+        let l = self.lca(a, b);
+        wdepth[a] + wdepth[b] - wdepth[l] - wdepth[l]
+    }
 
-            for &to in &g[i] {
-                if Some(to) != p {
-                    stack.push((to, Some(i)));
-                    childs[i].push(to);
-                }
+    pub fn kth_ancestor(&self, mut v: usize, k: usize) -> Option<usize> {
+        if k > self.depth[v] {
+            return None;
+        }
+        for i in 0..self.parent[v].len() {
+            if k >> i & 1 == 1 {
+                v = self.parent[v][i].unwrap();
             }
         }
+        Some(v)
+    }
 
-        Tree {
-            root,
-            parent,
-            childs,
+    /// `k`-th vertex on the path from `a` to `b`. The 0-th vertex is `a`
+    pub fn jump(&self, a: usize, b: usize, k: usize) -> Option<usize> {
+        let l = self.lca(a, b);
+        let da = self.depth[a] - self.depth[l];
+        let db = self.depth[b] - self.depth[l];
+        if k <= da {
+            self.kth_ancestor(a, k)
+        } else if k <= da + db {
+            self.kth_ancestor(b, da + db - k)
+        } else {
+            None
         }
     }
 }
 
-#[snippet("HeavyLightDecomposition")]
-pub struct HeavyLightDecomposition {
-    pub ids: Vec<(usize, usize)>,
-    pub parents: Vec<Option<(usize, usize)>>,
-    pub parts: Vec<Vec<usize>>,
+#[snippet("LCAFold")]
+#[snippet(include = "LCA")]
+#[snippet(include = "Graph")]
+#[snippet(include = "Monoid")]
+#[allow(dead_code)]
+/// Binary lifting with monoid aggregates of edge values along the way
+pub struct LCAFold<M: Monoid> {
+    pub lca: LCA,
+    /// up[v][i]: fold of 2^i edges above v, from v upward
+    up: Vec<Vec<M::T>>,
+    /// down[v][i]: fold of 2^i edges above v, from the ancestor downward
+    down: Vec<Vec<M::T>>,
 }
 
-#[snippet("HeavyLightDecomposition")]
-impl HeavyLightDecomposition {
-    pub fn new(tree: &Tree) -> HeavyLightDecomposition {
-        fn size(i: usize, tree: &Tree, memo: &mut [Option<usize>]) -> usize {
-            if let Some(res) = memo[i] {
-                return res;
+#[snippet("LCAFold")]
+#[allow(dead_code)]
+impl<M: Monoid> LCAFold<M> {
+    /// Build from a tree whose edge weights are monoid values
+    pub fn new(root: usize, g: &Graph<M::T>) -> LCAFold<M> {
+        let n = g.len();
+        let lca = LCA::with_root(root, g);
+        let l = lca.parent[0].len();
+
+        let mut val = vec![M::id(); n];
+        let mut stack = vec![(root, !0)];
+        while let Some((v, p)) = stack.pop() {
+            for &(to, id) in g.adj(v) {
+                if to != p {
+                    val[to] = g.edges[id].2.clone();
+                    stack.push((to, v));
+                }
             }
+        }
 
-            let res = tree.childs[i]
-                .iter()
-                .map(|&to| size(to, tree, memo))
-                .sum::<usize>()
-                + 1;
-            memo[i] = Some(res);
-            res
+        let mut up = val.iter().map(|x| vec![x.clone(); l]).collect::<Vec<_>>();
+        let mut down = up.clone();
+        for i in 1..l {
+            for v in 0..n {
+                if let Some(p) = lca.parent[v][i - 1] {
+                    up[v][i] = M::op(&up[v][i - 1], &up[p][i - 1]);
+                    down[v][i] = M::op(&down[p][i - 1], &down[v][i - 1]);
+                }
+            }
         }
 
-        let n = tree.parent.len();
-        let root = tree.root;
-        let mut memo = vec![None; n];
+        LCAFold { lca, up, down }
+    }
 
-        let mut ids = vec![(0, 0); n];
-        let mut parts: Vec<Vec<usize>> = Vec::new();
-        let mut stack = vec![(root, false, None)];
-        let mut parents = Vec::new();
+    /// Fold of `k` edges above `v`, from `v` upward
+    pub fn fold_up(&self, mut v: usize, k: usize) -> M::T {
+        let mut res = M::id();
+        for i in 0..self.up[v].len() {
+            if k >> i & 1 == 1 {
+                res = M::op(&res, &self.up[v][i]);
+                v = self.lca.parent[v][i].unwrap();
+            }
+        }
+        res
+    }
 
-        while let Some((i, h, pid)) = stack.pop() {
-            if h {
-                let (k, _) = pid.unwrap();
-                ids[i] = (k, parts[k].len());
-                parts[k].push(i);
-            } else {
-                ids[i] = (parts.len(), 0);
-                parts.push(vec![i]);
-                parents.push(pid);
+    /// Fold of `k` edges above `v`, from the ancestor downward
+    pub fn fold_down(&self, mut v: usize, k: usize) -> M::T {
+        let mut res = M::id();
+        for i in 0..self.down[v].len() {
+            if k >> i & 1 == 1 {
+                res = M::op(&self.down[v][i], &res);
+                v = self.lca.parent[v][i].unwrap();
             }
+        }
+        res
+    }
 
-            let id = ids[i];
+    /// Fold of the edges on the path from `a` to `b` in order
+    pub fn path_fold(&self, a: usize, b: usize) -> M::T {
+        let l = self.lca.lca(a, b);
+        let da = self.lca.depth[a] - self.lca.depth[l];
+        let db = self.lca.depth[b] - self.lca.depth[l];
+        M::op(&self.fold_up(a, da), &self.fold_down(b, db))
+    }
+}
 
-            let heavy = tree.childs[i]
-                .iter()
-                .max_by_key(|&&to| size(to, &tree, &mut memo));
+#[snippet("EulerTourLCA")]
+#[snippet(include = "Adjacency")]
+#[allow(dead_code)]
+/// LCA by Euler tour and sparse table. O(N log N) construction, O(1) query
+pub struct EulerTourLCA {
+    pub depth: Vec<usize>,
+    /// Pre-order number of each vertex
+    pub tin: Vec<usize>,
+    /// Vertices of each depth in pre-order
+    by_depth: Vec<Vec<usize>>,
+    /// First occurrence of each vertex in the Euler tour
+    first: Vec<usize>,
+    /// table[k][i] is the shallowest vertex in tour[i..i + 2^k]
+    table: Vec<Vec<usize>>,
+}
 
-            for &to in &tree.childs[i] {
-                if Some(&to) != heavy {
-                    stack.push((to, false, Some(id)));
+#[snippet("EulerTourLCA")]
+#[allow(dead_code)]
+impl EulerTourLCA {
+    pub fn new<G: Adjacency + ?Sized>(root: usize, g: &G) -> EulerTourLCA {
+        let n = g.vertices();
+        let mut depth = vec![0; n];
+        let mut tin = vec![0; n];
+        let mut by_depth = vec![Vec::new()];
+        let mut first = vec![0; n];
+        let mut tour = Vec::with_capacity(2 * n);
+
+        let mut t = 0;
+        // vertex, parent, next edge index
+        let mut stack = vec![(root, !0, 0)];
+        by_depth[0].push(root);
+        first[root] = 0;
+        tour.push(root);
+        t += 1;
+        while let Some(&mut (v, p, ref mut i)) = stack.last_mut() {
+            if *i < g.degree(v) {
+                let to = g.neighbor(v, *i);
+                *i += 1;
+                if to != p {
+                    depth[to] = depth[v] + 1;
+                    tin[to] = t;
+                    t += 1;
+                    if by_depth.len() <= depth[to] {
+                        by_depth.push(Vec::new());
+                    }
+                    by_depth[depth[to]].push(to);
+                    first[to] = tour.len();
+                    tour.push(to);
+                    stack.push((to, v, 0));
+                }
+            } else {
+                stack.pop();
+                if let Some(&(u, _, _)) = stack.last() {
+                    tour.push(u);
                 }
             }
+        }
 
-            if let Some(&h) = heavy {
-                stack.push((h, true, Some(id)));
-            }
+        let mut table = vec![tour];
+        let mut k = 1;
+        while 2 * k <= table[0].len() {
+            let prev = table.last().unwrap();
+            let row = (0..prev.len() - k)
+                .map(|i| {
+                    let (a, b) = (prev[i], prev[i + k]);
+                    if depth[a] <= depth[b] {
+                        a
+                    } else {
+                        b
+                    }
+                })
+                .collect();
+            table.push(row);
+            k *= 2;
         }
 
-        HeavyLightDecomposition {
-            ids,
-            parents,
-            parts,
+        EulerTourLCA {
+            depth,
+            tin,
+            by_depth,
+            first,
+            table,
         }
     }
-}
 
-use std::cmp::min;
+    pub fn lca(&self, a: usize, b: usize) -> usize {
+        let (l, r) = {
+            let (x, y) = (self.first[a], self.first[b]);
+            if x <= y {
+                (x, y + 1)
+            } else {
+                (y, x + 1)
+            }
+        };
+        let k = (0usize.leading_zeros() - (r - l).leading_zeros() - 1) as usize;
+        let (x, y) = (self.table[k][l], self.table[k][r - (1 << k)]);
+        if self.depth[x] <= self.depth[y] {
+            x
+        } else {
+            y
+        }
+    }
 
-#[snippet("articulation_points")]
-pub fn articulation_points(g: &[Vec<usize>]) -> Vec<usize> {
-    #[allow(clippy::too_many_arguments)]
-    fn dfs(
-        i: usize,
-        p: usize,
-        g: &[Vec<usize>],
-        t: usize,
-        visited: &mut [bool],
-        prenum: &mut [usize],
-        parent: &mut [usize],
-        lowest: &mut [usize],
-    ) {
-        prenum[i] = t;
-        lowest[i] = t;
-        visited[i] = true;
-
-        for &to in &g[i] {
-            if !visited[to] {
-                parent[to] = i;
-                dfs(to, i, g, t + 1, visited, prenum, parent, lowest);
-                lowest[i] = min(lowest[i], lowest[to]);
-            } else if to != p {
-                lowest[i] = min(lowest[i], prenum[to]);
-            }
-        }
-    }
-
-    let n = g.len();
-    let mut visited = vec![false; n];
-    let mut prenum = vec![0; n];
-    let mut parent = vec![0; n];
-    let mut lowest = vec![0; n];
-
-    dfs(
-        0,
-        0,
-        g,
-        1,
-        &mut visited,
-        &mut prenum,
-        &mut parent,
-        &mut lowest,
-    );
+    /// Number of edges between `a` and `b`
+    pub fn dist(&self, a: usize, b: usize) -> usize {
+        self.depth[a] + self.depth[b] - 2 * self.depth[self.lca(a, b)]
+    }
 
-    let mut res = if (1..n).filter(|&i| parent[i] == 0).count() >= 2 {
-        vec![0]
-    } else {
-        Vec::new()
-    };
+    /// `wdepth` is the weighted depth of each vertex. See `weighted_depth`
+    pub fn weighted_dist<T>(&self, a: usize, b: usize, wdepth: &[T]) -> T
+    where
+        T: Copy + std::ops::Add<Output = T> + std::ops::Sub<Output = T>,
+    {
+        let l = self.lca(a, b);
+        wdepth[a] + wdepth[b] - wdepth[l] - wdepth[l]
+    }
 
-    for i in 1..n {
-        let p = parent[i];
-        if p != 0 && prenum[p] <= lowest[i] {
-            res.push(p);
+    /// O(log N)
+    pub fn kth_ancestor(&self, v: usize, k: usize) -> Option<usize> {
+        if k > self.depth[v] {
+            return None;
         }
+        // The ancestor is the last vertex of its depth which precedes v in pre-order
+        let vs = &self.by_depth[self.depth[v] - k];
+        let i = match vs.binary_search_by_key(&self.tin[v], |&u| self.tin[u]) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        Some(vs[i])
     }
 
-    res.sort();
-    res.dedup();
-
-    res
+    /// `k`-th vertex on the path from `a` to `b`. The 0-th vertex is `a`
+    pub fn jump(&self, a: usize, b: usize, k: usize) -> Option<usize> {
+        let l = self.lca(a, b);
+        let da = self.depth[a] - self.depth[l];
+        let db = self.depth[b] - self.depth[l];
+        if k <= da {
+            self.kth_ancestor(a, k)
+        } else if k <= da + db {
+            self.kth_ancestor(b, da + db - k)
+        } else {
+            None
+        }
+    }
 }
 
-#[snippet("bridges")]
-pub fn bridges(g: &[Vec<usize>]) -> Vec<(usize, usize)> {
-    #[allow(clippy::too_many_arguments)]
-    fn dfs(
-        i: usize,
-        p: usize,
-        g: &[Vec<usize>],
-        t: usize,
-        visited: &mut [bool],
-        pre: &mut [usize],
-        low: &mut [usize],
-        res: &mut Vec<(usize, usize)>,
-    ) -> usize {
-        visited[i] = true;
-        pre[i] = t;
-        low[i] = t;
-
-        for &to in &g[i] {
-            if p != to {
-                if visited[to] {
-                    low[i] = min(low[i], low[to]);
-                } else {
-                    low[i] = min(low[i], dfs(to, i, g, t + 1, visited, pre, low, res));
+#[snippet("weighted_depth")]
+#[snippet(include = "Graph")]
+#[allow(dead_code)]
+/// Sum of edge weights from `root` to each vertex of a tree
+pub fn weighted_depth<W>(root: usize, g: &Graph<W>) -> Vec<W>
+where
+    W: Copy + Default + std::ops::Add<Output = W>,
+{
+    let mut res = vec![W::default(); g.len()];
+    let mut stack = vec![(root, !0)];
+    while let Some((v, p)) = stack.pop() {
+        for &(to, id) in g.adj(v) {
+            if to != p {
+                res[to] = res[v] + g.edges[id].2;
+                stack.push((to, v));
+            }
+        }
+    }
+    res
+}
+
+#[snippet("Tree")]
+#[snippet("HeavyLightDecomposition")]
+#[derive(Clone, Debug)]
+/// Rooted tree. Everything is computed without recursion.
+pub struct Tree {
+    pub root: usize,
+    pub parent: Vec<Option<usize>>,
+    pub childs: Vec<Vec<usize>>,
+    pub depth: Vec<usize>,
+    /// Number of vertices in the subtree
+    pub size: Vec<usize>,
+    pub bfs_order: Vec<usize>,
+    /// Pre-order. The subtree of v is `dfs_order[tin[v]..tout[v]]`
+    pub dfs_order: Vec<usize>,
+    pub tin: Vec<usize>,
+    pub tout: Vec<usize>,
+}
+
+#[snippet("Tree")]
+#[snippet("HeavyLightDecomposition")]
+impl Tree {
+    pub fn from_neighbor_list(n: usize, root: usize, g: &[Vec<usize>]) -> Tree {
+        let mut parent = vec![None; n];
+        let mut childs = vec![Vec::new(); n];
+
+        let mut stack = vec![(root, None)];
+
+        while let Some((i, p)) = stack.pop() {
+            parent[i] = p;
+
+            for &to in &g[i] {
+                if Some(to) != p {
+                    stack.push((to, Some(i)));
+                    childs[i].push(to);
+                }
+            }
+        }
+
+        Tree::build(root, parent, childs)
+    }
+
+    #[allow(dead_code)]
+    /// The root is the only vertex without a parent
+    pub fn from_parent(parent: &[Option<usize>]) -> Tree {
+        let n = parent.len();
+        let mut childs = vec![Vec::new(); n];
+        let mut root = 0;
+        for (v, p) in parent.iter().enumerate() {
+            match *p {
+                Some(p) => childs[p].push(v),
+                None => root = v,
+            }
+        }
+        Tree::build(root, parent.to_vec(), childs)
+    }
 
-                    if low[to] == pre[to] {
-                        res.push((i, to));
+    #[allow(dead_code)]
+    /// Returns the tree and the weight of the edge (parent[v], v) for each v.
+    /// The weight of the root is `W::default()`.
+    pub fn from_weighted_edges<W: Clone + Default>(
+        n: usize,
+        root: usize,
+        edges: &[(usize, usize, W)],
+    ) -> (Tree, Vec<W>) {
+        let mut g = vec![Vec::new(); n];
+        for &(u, v, _) in edges {
+            g[u].push(v);
+            g[v].push(u);
+        }
+        let tree = Tree::from_neighbor_list(n, root, &g);
+        let mut weight = vec![W::default(); n];
+        for &(u, v, ref w) in edges {
+            if tree.parent[v] == Some(u) {
+                weight[v] = w.clone();
+            } else {
+                weight[u] = w.clone();
+            }
+        }
+        (tree, weight)
+    }
+
+    fn build(root: usize, parent: Vec<Option<usize>>, childs: Vec<Vec<usize>>) -> Tree {
+        let n = parent.len();
+        let mut depth = vec![0; n];
+        let mut bfs_order = vec![root];
+        let mut k = 0;
+        while k < bfs_order.len() {
+            let v = bfs_order[k];
+            k += 1;
+            for &to in &childs[v] {
+                depth[to] = depth[v] + 1;
+                bfs_order.push(to);
+            }
+        }
+
+        let mut size = vec![1; n];
+        for &v in bfs_order.iter().rev() {
+            if let Some(p) = parent[v] {
+                size[p] += size[v];
+            }
+        }
+
+        let mut dfs_order = Vec::with_capacity(n);
+        // Vertices not under `root` get the empty interval [n, n)
+        let mut tin = vec![n; n];
+        let mut tout = vec![n; n];
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            tin[v] = dfs_order.len();
+            tout[v] = tin[v] + size[v];
+            dfs_order.push(v);
+            stack.extend(childs[v].iter().rev());
+        }
+
+        Tree {
+            root,
+            parent,
+            childs,
+            depth,
+            size,
+            bfs_order,
+            dfs_order,
+            tin,
+            tout,
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Whether `u` is an ancestor of `v` (or `v` itself)
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        self.tin[u] <= self.tin[v] && self.tin[v] < self.tout[u]
+    }
+}
+
+#[snippet("Tree")]
+impl Tree {
+    #[allow(dead_code)]
+    /// Weighted depth of each vertex. `weight[v]` is the weight of the edge (parent[v], v).
+    pub fn weighted_depth<W: Copy + Default + std::ops::Add<Output = W>>(
+        &self,
+        weight: &[W],
+    ) -> Vec<W> {
+        let mut res = vec![W::default(); self.parent.len()];
+        for &v in &self.bfs_order {
+            if let Some(p) = self.parent[v] {
+                res[v] = res[p] + weight[v];
+            }
+        }
+        res
+    }
+
+    /// Distances from `s` and the previous vertex on the shortest path.
+    /// `weight[v]` is the weight of the edge (parent[v], v).
+    fn distances<W: Copy + Default + std::ops::Add<Output = W>>(
+        &self,
+        s: usize,
+        weight: &[W],
+    ) -> (Vec<W>, Vec<usize>) {
+        let n = self.parent.len();
+        let mut dist = vec![W::default(); n];
+        let mut prev = vec![!0; n];
+        let mut stack = vec![s];
+        while let Some(v) = stack.pop() {
+            if let Some(p) = self.parent[v] {
+                if p != prev[v] {
+                    dist[p] = dist[v] + weight[v];
+                    prev[p] = v;
+                    stack.push(p);
+                }
+            }
+            for &to in &self.childs[v] {
+                if to != prev[v] {
+                    dist[to] = dist[v] + weight[to];
+                    prev[to] = v;
+                    stack.push(to);
+                }
+            }
+        }
+        (dist, prev)
+    }
+
+    #[allow(dead_code)]
+    /// Longest path with non-negative weights. `weight[v]` is the weight of the edge (parent[v], v).
+    /// Returns (length, path). The endpoints are the first and the last vertices of the path.
+    pub fn weighted_diameter<W: Copy + Default + Ord + std::ops::Add<Output = W>>(
+        &self,
+        weight: &[W],
+    ) -> (W, Vec<usize>) {
+        let far = |dist: &[W]| (0..dist.len()).max_by_key(|&v| dist[v]).unwrap();
+        let (dist, _) = self.distances(self.root, weight);
+        let s = far(&dist);
+        let (dist, prev) = self.distances(s, weight);
+        let t = far(&dist);
+
+        let mut path = vec![t];
+        while path[path.len() - 1] != s {
+            path.push(prev[path[path.len() - 1]]);
+        }
+        (dist[t], path)
+    }
+
+    #[allow(dead_code)]
+    /// Returns (number of edges, path)
+    pub fn diameter(&self) -> (usize, Vec<usize>) {
+        self.weighted_diameter(&vec![1; self.parent.len()])
+    }
+
+    #[allow(dead_code)]
+    /// Vertices minimizing the eccentricity. One or two adjacent vertices.
+    pub fn centers(&self) -> Vec<usize> {
+        let (_, path) = self.diameter();
+        let m = path.len();
+        if m % 2 == 1 {
+            vec![path[m / 2]]
+        } else {
+            vec![path[m / 2 - 1], path[m / 2]]
+        }
+    }
+}
+
+#[snippet("TreeHash")]
+#[snippet(include = "Tree")]
+#[allow(dead_code)]
+/// Randomized hash for tree isomorphism.
+/// The hash of a subtree whose height is h is prod (base[h] + hash of child) mod 2^61 - 1.
+pub struct TreeHash {
+    bases: Vec<u64>,
+}
+
+#[snippet("TreeHash")]
+impl TreeHash {
+    const MOD: u64 = (1 << 61) - 1;
+
+    #[allow(dead_code)]
+    /// Supports trees with at most `n` vertices
+    pub fn new(n: usize, seed: u64) -> TreeHash {
+        // splitmix64
+        let mut x = seed;
+        let bases = (0..n)
+            .map(|_| {
+                x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = x;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                z ^= z >> 31;
+                z % (TreeHash::MOD - 2) + 2
+            })
+            .collect();
+        TreeHash { bases }
+    }
+
+    fn mul(a: u64, b: u64) -> u64 {
+        (a as u128 * b as u128 % TreeHash::MOD as u128) as u64
+    }
+
+    #[allow(dead_code)]
+    /// Hash of the subtree of each vertex. Subtrees are isomorphic as rooted trees
+    /// iff their hashes are equal, with high probability.
+    pub fn rooted(&self, tree: &Tree) -> Vec<u64> {
+        let n = tree.parent.len();
+        let mut height = vec![0; n];
+        let mut hash = vec![1; n];
+        for &v in tree.bfs_order.iter().rev() {
+            let base = self.bases[height[v]];
+            hash[v] = tree.childs[v].iter().fold(1, |h, &c| {
+                TreeHash::mul(h, (base + hash[c]) % TreeHash::MOD)
+            });
+            if let Some(p) = tree.parent[v] {
+                height[p] = height[p].max(height[v] + 1);
+            }
+        }
+        hash
+    }
+
+    #[allow(dead_code)]
+    /// Hash as an unrooted tree: the smaller rooted hash over the centers
+    pub fn unrooted(&self, tree: &Tree) -> u64 {
+        let n = tree.parent.len();
+        let mut g = vec![Vec::new(); n];
+        for (v, p) in tree.parent.iter().enumerate() {
+            if let Some(p) = *p {
+                g[v].push(p);
+                g[p].push(v);
+            }
+        }
+        tree.centers()
+            .into_iter()
+            .map(|c| self.rooted(&Tree::from_neighbor_list(n, c, &g))[c])
+            .min()
+            .unwrap()
+    }
+}
+
+#[snippet("Tree")]
+#[snippet(include = "Adjacency")]
+/// Undirected view of the tree. The parent comes first.
+impl Adjacency for Tree {
+    fn vertices(&self) -> usize {
+        self.parent.len()
+    }
+    fn degree(&self, v: usize) -> usize {
+        self.childs[v].len() + self.parent[v].is_some() as usize
+    }
+    fn neighbor(&self, v: usize, i: usize) -> usize {
+        match self.parent[v] {
+            Some(p) if i == 0 => p,
+            Some(_) => self.childs[v][i - 1],
+            None => self.childs[v][i],
+        }
+    }
+}
+
+#[snippet("virtual_tree")]
+#[snippet(include = "Tree")]
+#[allow(dead_code)]
+/// Auxiliary tree of `vertices` and the LCAs of them. O(k log k) calls of `lca`.
+/// `lca` can be `LCA::lca` or `EulerTourLCA::lca` of the same tree.
+/// `depth` is `tree.depth` to count original edges, or `tree.weighted_depth(&weight)`.
+/// Returns (nodes in pre-order, edges (parent, child, length of the original path)).
+/// The first node is the root.
+pub fn virtual_tree<W: Copy + std::ops::Sub<Output = W>, F: Fn(usize, usize) -> usize>(
+    tree: &Tree,
+    lca: F,
+    depth: &[W],
+    vertices: &[usize],
+) -> (Vec<usize>, Vec<(usize, usize, W)>) {
+    let mut nodes = vertices.to_vec();
+    nodes.sort_by_key(|&v| tree.tin[v]);
+    nodes.dedup();
+    let ls = nodes
+        .windows(2)
+        .map(|w| lca(w[0], w[1]))
+        .collect::<Vec<_>>();
+    nodes.extend(ls);
+    nodes.sort_by_key(|&v| tree.tin[v]);
+    nodes.dedup();
+
+    let mut edges = Vec::with_capacity(nodes.len().saturating_sub(1));
+    let mut stack: Vec<usize> = Vec::new();
+    for &v in &nodes {
+        while let Some(&u) = stack.last() {
+            if tree.is_ancestor(u, v) {
+                edges.push((u, v, depth[v] - depth[u]));
+                break;
+            }
+            stack.pop();
+        }
+        stack.push(v);
+    }
+
+    (nodes, edges)
+}
+
+#[snippet("HeavyLightDecomposition")]
+pub struct HeavyLightDecomposition {
+    pub ids: Vec<(usize, usize)>,
+    pub parents: Vec<Option<(usize, usize)>>,
+    pub parts: Vec<Vec<usize>>,
+    /// Index of each vertex in a pre-order visiting heavy children first.
+    /// Every heavy path and every subtree is a contiguous interval.
+    pub tin: Vec<usize>,
+    /// Subtree of v is tin[v]..tout[v]
+    pub tout: Vec<usize>,
+    /// Topmost vertex of the heavy path containing each vertex
+    pub head: Vec<usize>,
+}
+
+#[snippet("HeavyLightDecomposition")]
+impl HeavyLightDecomposition {
+    pub fn new(tree: &Tree) -> HeavyLightDecomposition {
+        let n = tree.parent.len();
+        let root = tree.root;
+
+        let mut ids = vec![(0, 0); n];
+        let mut parts: Vec<Vec<usize>> = Vec::new();
+        let mut stack = vec![(root, false, None)];
+        let mut parents = Vec::new();
+        let mut tin = vec![0; n];
+        let mut tout = vec![0; n];
+        let mut t = 0;
+
+        while let Some((i, h, pid)) = stack.pop() {
+            tin[i] = t;
+            tout[i] = t + tree.size[i];
+            t += 1;
+            if h {
+                let (k, _) = pid.unwrap();
+                ids[i] = (k, parts[k].len());
+                parts[k].push(i);
+            } else {
+                ids[i] = (parts.len(), 0);
+                parts.push(vec![i]);
+                parents.push(pid);
+            }
+
+            let id = ids[i];
+
+            let heavy = tree.childs[i].iter().max_by_key(|&&to| tree.size[to]);
+
+            for &to in &tree.childs[i] {
+                if Some(&to) != heavy {
+                    stack.push((to, false, Some(id)));
+                }
+            }
+
+            if let Some(&h) = heavy {
+                stack.push((h, true, Some(id)));
+            }
+        }
+
+        let head = ids.iter().map(|&(k, _)| parts[k][0]).collect();
+
+        HeavyLightDecomposition {
+            ids,
+            parents,
+            parts,
+            tin,
+            tout,
+            head,
+        }
+    }
+
+    /// Parent of the head of the heavy path containing `v`
+    fn above_head(&self, v: usize) -> usize {
+        let (k, i) = self.parents[self.ids[v].0].unwrap();
+        self.parts[k][i]
+    }
+
+    pub fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        while self.head[u] != self.head[v] {
+            if self.tin[self.head[u]] > self.tin[self.head[v]] {
+                u = self.above_head(u);
+            } else {
+                v = self.above_head(v);
+            }
+        }
+        if self.tin[u] < self.tin[v] {
+            u
+        } else {
+            v
+        }
+    }
+
+    /// Intervals of `tin` indices covering the path from `u` to `v`, in order.
+    /// `(l, r, true)` means the interval is traversed from r - 1 down to l.
+    /// If `edge` is false, every vertex on the path is covered.
+    /// If `edge` is true, only the LCA of `u` and `v` is left out, so the intervals cover
+    /// exactly the edges of the path when edge (parent[w], w) is stored at tin[w].
+    /// Then `u == v` gives no interval.
+    pub fn path_ranges(&self, mut u: usize, mut v: usize, edge: bool) -> Vec<(usize, usize, bool)> {
+        let mut up = Vec::new();
+        let mut down = Vec::new();
+        while self.head[u] != self.head[v] {
+            if self.tin[self.head[u]] > self.tin[self.head[v]] {
+                up.push((self.tin[self.head[u]], self.tin[u] + 1, true));
+                u = self.above_head(u);
+            } else {
+                down.push((self.tin[self.head[v]], self.tin[v] + 1, false));
+                v = self.above_head(v);
+            }
+        }
+        let e = edge as usize;
+        if self.tin[u] >= self.tin[v] {
+            up.push((self.tin[v] + e, self.tin[u] + 1, true));
+        } else {
+            down.push((self.tin[u] + e, self.tin[v] + 1, false));
+        }
+
+        up.extend(down.into_iter().rev());
+        up.retain(|&(l, r, _)| l < r);
+        up
+    }
+
+    /// Interval of `tin` indices of the subtree of `v`.
+    /// If `edge` is true, `v` itself is excluded, leaving the edges below `v`
+    /// when edge (parent[w], w) is stored at tin[w].
+    pub fn subtree_range(&self, v: usize, edge: bool) -> (usize, usize) {
+        (self.tin[v] + edge as usize, self.tout[v])
+    }
+}
+
+#[snippet("CentroidDecomposition")]
+#[snippet(include = "Adjacency")]
+#[derive(Clone, Debug)]
+/// Centroid decomposition of a tree (or a forest) without recursion.
+pub struct CentroidDecomposition {
+    /// Parent in the centroid tree
+    pub parent: Vec<Option<usize>>,
+    /// Depth in the centroid tree. At most log2(N)
+    pub depth: Vec<usize>,
+    /// Centroids in the order they were chosen. Parents come first
+    pub order: Vec<usize>,
+}
+
+#[snippet("CentroidDecomposition")]
+impl CentroidDecomposition {
+    pub fn new<G: Adjacency + ?Sized>(g: &G) -> CentroidDecomposition {
+        let n = g.vertices();
+        let mut parent = vec![None; n];
+        let mut depth = vec![!0; n];
+        let mut order = Vec::with_capacity(n);
+        let mut size = vec![0; n];
+        let mut bfs_parent = vec![!0; n];
+        let mut queue = Vec::new();
+        let mut stack = (0..n).rev().map(|v| (v, None)).collect::<Vec<_>>();
+
+        while let Some((s, p)) = stack.pop() {
+            if depth[s] != !0 {
+                continue;
+            }
+
+            queue.clear();
+            queue.push(s);
+            bfs_parent[s] = !0;
+            let mut k = 0;
+            while k < queue.len() {
+                let v = queue[k];
+                k += 1;
+                for i in 0..g.degree(v) {
+                    let to = g.neighbor(v, i);
+                    if to != bfs_parent[v] && depth[to] == !0 {
+                        bfs_parent[to] = v;
+                        queue.push(to);
+                    }
+                }
+            }
+
+            let total = queue.len();
+            let mut c = s;
+            for &v in queue.iter().rev() {
+                size[v] = 1;
+                let mut max = 0;
+                for i in 0..g.degree(v) {
+                    let to = g.neighbor(v, i);
+                    if to != bfs_parent[v] && depth[to] == !0 {
+                        size[v] += size[to];
+                        max = max.max(size[to]);
                     }
                 }
+                if 2 * max.max(total - size[v]) <= total {
+                    c = v;
+                }
+            }
+
+            parent[c] = p;
+            depth[c] = p.map(|p| depth[p] + 1).unwrap_or(0);
+            order.push(c);
+            for i in 0..g.degree(c) {
+                let to = g.neighbor(c, i);
+                if depth[to] == !0 {
+                    stack.push((to, Some(c)));
+                }
             }
         }
 
-        low[i]
+        CentroidDecomposition {
+            parent,
+            depth,
+            order,
+        }
     }
 
-    let n = g.len();
-    let mut visited = vec![false; n];
-    let mut pre = vec![0; n];
-    let mut low = vec![0; n];
-    let mut res = Vec::new();
+    /// Vertices of the component whose centroid is `c`, with the distance to `c`.
+    /// `c` comes first and distances are non-decreasing.
+    pub fn component<G: Adjacency + ?Sized>(&self, g: &G, c: usize) -> Vec<(usize, usize)> {
+        self.walk(g, c, c, 0)
+    }
 
-    dfs(0, 0, g, 0, &mut visited, &mut pre, &mut low, &mut res);
+    /// Parts of the component of `c` hanging from each neighbor of `c`,
+    /// with the distance to `c`.
+    pub fn subtrees<G: Adjacency + ?Sized>(&self, g: &G, c: usize) -> Vec<Vec<(usize, usize)>> {
+        (0..g.degree(c))
+            .map(|i| g.neighbor(c, i))
+            .filter(|&to| self.depth[to] > self.depth[c])
+            .map(|to| self.walk(g, c, to, 1))
+            .collect()
+    }
+
+    /// BFS from `s` inside the component of `c`
+    fn walk<G: Adjacency + ?Sized>(
+        &self,
+        g: &G,
+        c: usize,
+        s: usize,
+        d: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut res = vec![(s, d)];
+        let mut prev = vec![!0];
+        let mut k = 0;
+        while k < res.len() {
+            let (v, dv) = res[k];
+            let p = prev[k];
+            k += 1;
+            for i in 0..g.degree(v) {
+                let to = g.neighbor(v, i);
+                if to != p && self.depth[to] > self.depth[c] {
+                    res.push((to, dv + 1));
+                    prev.push(v);
+                }
+            }
+        }
+        res
+    }
+}
+
+#[snippet("count_paths_at_most")]
+#[snippet(include = "CentroidDecomposition")]
+#[allow(dead_code)]
+/// Number of unordered pairs of distinct vertices whose distance is at most `k`,
+/// by centroid decomposition. O(N log^2 N)
+pub fn count_paths_at_most<G: Adjacency + ?Sized>(g: &G, k: usize) -> usize {
+    fn pairs(mut ds: Vec<usize>, k: usize) -> usize {
+        ds.sort();
+        let mut res = 0;
+        let mut r = ds.len();
+        for l in 0..ds.len() {
+            while r > 0 && ds[l] + ds[r - 1] > k {
+                r -= 1;
+            }
+            if r <= l {
+                break;
+            }
+            res += r - l - 1;
+        }
+        res
+    }
+
+    let cd = CentroidDecomposition::new(g);
+    let mut res = 0;
+    for &c in &cd.order {
+        res += pairs(cd.component(g, c).iter().map(|&(_, d)| d).collect(), k);
+        for sub in cd.subtrees(g, c) {
+            res -= pairs(sub.iter().map(|&(_, d)| d).collect(), k);
+        }
+    }
     res
 }
 
+#[snippet("LowLink")]
+#[snippet(include = "Graph")]
+#[derive(Clone, Debug)]
+/// Low-link of an undirected graph computed by one iterative DFS.
+/// Handles disconnected graphs and parallel edges.
+pub struct LowLink {
+    /// Pre-order number
+    pub ord: Vec<usize>,
+    pub low: Vec<usize>,
+    /// Edge id to the DFS parent
+    pub parent_edge: Vec<Option<usize>>,
+    /// Sorted
+    pub articulation_points: Vec<usize>,
+    /// Edge ids of bridges
+    pub bridges: Vec<usize>,
+    /// Two-edge-connected component id of each vertex
+    pub tecc: Vec<usize>,
+    pub tecc_count: usize,
+    /// Biconnected components as sets of edge ids. Self loops and isolated vertices are not included.
+    pub bcc: Vec<Vec<usize>>,
+}
+
+#[snippet("LowLink")]
+impl LowLink {
+    #[allow(dead_code)]
+    pub fn new<W>(g: &Graph<W>) -> LowLink {
+        let n = g.len();
+        let mut ord = vec![!0; n];
+        let mut low = vec![!0; n];
+        let mut parent_edge = vec![None; n];
+        let mut is_articulation = vec![false; n];
+        let mut bridges = Vec::new();
+        let mut bcc = Vec::new();
+        let mut preorder = Vec::with_capacity(n);
+        let mut edge_stack = Vec::new();
+
+        for r in 0..n {
+            if ord[r] != !0 {
+                continue;
+            }
+            ord[r] = preorder.len();
+            low[r] = ord[r];
+            preorder.push(r);
+            let mut root_childs = 0;
+            // vertex, next edge index
+            let mut stack = vec![(r, 0)];
+
+            while let Some(&mut (v, ref mut i)) = stack.last_mut() {
+                if let Some(&(to, id)) = g.adj(v).get(*i) {
+                    *i += 1;
+                    if Some(id) == parent_edge[v] {
+                        continue;
+                    }
+                    if ord[to] == !0 {
+                        ord[to] = preorder.len();
+                        low[to] = ord[to];
+                        preorder.push(to);
+                        parent_edge[to] = Some(id);
+                        edge_stack.push(id);
+                        stack.push((to, 0));
+                    } else if ord[to] < ord[v] {
+                        low[v] = low[v].min(ord[to]);
+                        edge_stack.push(id);
+                    }
+                } else {
+                    stack.pop();
+                    let p = match stack.last() {
+                        Some(&(p, _)) => p,
+                        None => break,
+                    };
+                    low[p] = low[p].min(low[v]);
+                    let pe = parent_edge[v].unwrap();
+                    if low[v] > ord[p] {
+                        bridges.push(pe);
+                    }
+                    if low[v] >= ord[p] {
+                        if p == r {
+                            root_childs += 1;
+                        } else {
+                            is_articulation[p] = true;
+                        }
+                        let mut comp = Vec::new();
+                        while let Some(e) = edge_stack.pop() {
+                            comp.push(e);
+                            if e == pe {
+                                break;
+                            }
+                        }
+                        bcc.push(comp);
+                    }
+                }
+            }
+
+            if root_childs >= 2 {
+                is_articulation[r] = true;
+            }
+        }
+
+        let mut tecc = vec![0; n];
+        let mut tecc_count = 0;
+        for &v in &preorder {
+            match parent_edge[v] {
+                Some(e) if low[v] <= ord[g.opposite(e, v)] => {
+                    tecc[v] = tecc[g.opposite(e, v)];
+                }
+                _ => {
+                    tecc[v] = tecc_count;
+                    tecc_count += 1;
+                }
+            }
+        }
+
+        LowLink {
+            ord,
+            low,
+            parent_edge,
+            articulation_points: (0..n).filter(|&v| is_articulation[v]).collect(),
+            bridges,
+            tecc,
+            tecc_count,
+            bcc,
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Tree of two-edge-connected components connected by bridges
+    pub fn bridge_tree<W>(&self, g: &Graph<W>) -> Vec<Vec<usize>> {
+        let mut res = vec![Vec::new(); self.tecc_count];
+        for &e in &self.bridges {
+            let (u, v, _) = g.edges[e];
+            res[self.tecc[u]].push(self.tecc[v]);
+            res[self.tecc[v]].push(self.tecc[u]);
+        }
+        res
+    }
+
+    #[allow(dead_code)]
+    /// Block-cut tree. Vertex v of `g` is node v and the i-th biconnected component is node n + i.
+    /// Each vertex is connected to the components containing it.
+    pub fn block_cut_tree<W>(&self, g: &Graph<W>) -> Vec<Vec<usize>> {
+        let n = g.len();
+        let mut res = vec![Vec::new(); n + self.bcc.len()];
+        for (i, comp) in self.bcc.iter().enumerate() {
+            let mut vs = comp
+                .iter()
+                .flat_map(|&e| {
+                    let (u, v, _) = g.edges[e];
+                    vec![u, v]
+                })
+                .collect::<Vec<_>>();
+            vs.sort();
+            vs.dedup();
+            for v in vs {
+                res[v].push(n + i);
+                res[n + i].push(v);
+            }
+        }
+        res
+    }
+}
+
+#[snippet("articulation_points")]
+#[snippet(include = "LowLink")]
+pub fn articulation_points<G: Adjacency + ?Sized>(g: &G) -> Vec<usize> {
+    LowLink::new(&Graph::from_adjacency(g)).articulation_points
+}
+
+#[snippet("bridges")]
+#[snippet(include = "LowLink")]
+/// Returns (parent, child) in the DFS tree for each bridge
+pub fn bridges<G: Adjacency + ?Sized>(g: &G) -> Vec<(usize, usize)> {
+    let g = Graph::from_adjacency(g);
+    let lowlink = LowLink::new(&g);
+    lowlink
+        .bridges
+        .iter()
+        .map(|&e| {
+            let (u, v, _) = g.edges[e];
+            if lowlink.ord[u] < lowlink.ord[v] {
+                (u, v)
+            } else {
+                (v, u)
+            }
+        })
+        .collect()
+}
+
 #[test]
 fn test_lca() {
     use rand::{Rng, SeedableRng, StdRng};
@@ -378,3 +1740,847 @@ fn test_lca() {
         assert_eq!(p, a);
     }
 }
+
+#[test]
+fn test_scc() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..50 {
+        let n = 30;
+        let mut g = vec![Vec::new(); n];
+        for _ in 0..45 {
+            let u = rng.next_u32() as usize % n;
+            let v = rng.next_u32() as usize % n;
+            g[u].push(v);
+        }
+
+        let reach = (0..n)
+            .map(|v| {
+                let mut r = vec![false; n];
+                let mut stack = vec![v];
+                r[v] = true;
+                while let Some(u) = stack.pop() {
+                    for &to in &g[u] {
+                        if !r[to] {
+                            r[to] = true;
+                            stack.push(to);
+                        }
+                    }
+                }
+                r
+            })
+            .collect::<Vec<_>>();
+
+        let scc = SCC::new(&g);
+        assert_eq!(scc.members.iter().map(|m| m.len()).sum::<usize>(), n);
+        for (u, ru) in reach.iter().enumerate() {
+            for (v, rv) in reach.iter().enumerate() {
+                assert_eq!(scc.cmp[u] == scc.cmp[v], ru[v] && rv[u]);
+            }
+        }
+        for (c, es) in scc.dag.iter().enumerate() {
+            for w in es.windows(2) {
+                assert!(w[0] < w[1]);
+            }
+            for &d in es {
+                assert!(c < d);
+            }
+        }
+    }
+
+    // A long path must not overflow the stack
+    let n = 1_000_000;
+    let g = (0..n)
+        .map(|i| if i + 1 < n { vec![i + 1] } else { vec![0] })
+        .collect::<Vec<_>>();
+    assert_eq!(SCC::new(&g).count, 1);
+}
+
+#[test]
+fn test_graph_csr() {
+    let edges = [(1, 2, 5u64), (2, 3, 7), (3, 1, 2), (3, 4, 1)];
+    let g = Graph::from_1indexed(5, &edges, true);
+    let adj = vec![vec![1], vec![2], vec![0, 3], vec![], vec![]];
+
+    for (v, a) in adj.iter().enumerate() {
+        assert_eq!(&g.adj(v).iter().map(|&(to, _)| to).collect::<Vec<_>>(), a);
+        for &(to, id) in g.adj(v) {
+            assert_eq!(g.edges[id].0, v);
+            assert_eq!(g.opposite(id, v), to);
+        }
+    }
+    assert_eq!(*g.weight(2), 2);
+    assert_eq!(g.reversed().adj(0), &[(2, 2)]);
+    assert_eq!(g.reverse_edge(0), None);
+
+    assert_eq!(
+        strongly_connected_component(&g),
+        strongly_connected_component(&adj)
+    );
+    assert_eq!(g.connected_components(), (2, vec![0, 0, 0, 0, 1]));
+
+    let u = Graph::unweighted(4, &[(0, 1), (1, 2), (2, 3)], false);
+    assert_eq!(u.degree(1), 2);
+    assert_eq!(u.bipartite_coloring(), Some(vec![false, true, false, true]));
+    let odd = Graph::unweighted(3, &[(0, 1), (1, 2), (2, 0)], false);
+    assert_eq!(odd.bipartite_coloring(), None);
+    assert_eq!(bridges(&u).len(), 3);
+    assert_eq!(articulation_points(&u), vec![1, 2]);
+}
+
+#[test]
+fn test_graph_edge_lookup() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..100 {
+        let n = rng.next_u32() as usize % 6 + 1;
+        let edges = (0..rng.next_u32() % 12)
+            .map(|_| (rng.next_u32() as usize % n, rng.next_u32() as usize % n, ()))
+            .collect::<Vec<_>>();
+        let d = Graph::directed(n, &edges);
+        let u = Graph::undirected(n, &edges);
+        for (id, &(a, b, _)) in edges.iter().enumerate() {
+            assert_eq!(d.opposite(id, a), b);
+            assert_eq!(d.opposite(id, b), a);
+            assert_eq!(u.reverse_edge(id), Some(id));
+            match d.reverse_edge(id) {
+                Some(e) => assert_eq!((edges[e].0, edges[e].1), (b, a)),
+                None => assert!(edges.iter().all(|e| (e.0, e.1) != (b, a))),
+            }
+        }
+    }
+
+    // Edges listed in only one direction are not dropped
+    let path = vec![vec![1], vec![2], vec![]];
+    let sym = vec![vec![1], vec![0, 2], vec![1]];
+    assert_eq!(Graph::from_adjacency(&path).edges.len(), 2);
+    assert_eq!(bridges(&path).len(), 2);
+    assert_eq!(articulation_points(&path), articulation_points(&sym));
+    // Parallel edges listed on both sides are counted once per pair
+    assert_eq!(
+        Graph::from_adjacency(&vec![vec![1, 1], vec![0, 0]])
+            .edges
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_lca_queries() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..20 {
+        let n = 100;
+        let edges = (1..n)
+            .map(|i| (rng.next_u32() as usize % i, i, rng.next_u64() % 100))
+            .collect::<Vec<_>>();
+        let g = Graph::undirected(n, &edges);
+        let root = rng.next_u32() as usize % n;
+        let lca = LCA::with_root(root, &g);
+        let et = EulerTourLCA::new(root, &g);
+        let wdepth = weighted_depth(root, &g);
+
+        let tree = Tree::from_neighbor_list(
+            n,
+            root,
+            &(0..n)
+                .map(|v| g.adj(v).iter().map(|&(to, _)| to).collect())
+                .collect::<Vec<_>>(),
+        );
+        let path_to_root = |mut v: usize| {
+            let mut res = vec![v];
+            while let Some(p) = tree.parent[v] {
+                res.push(p);
+                v = p;
+            }
+            res
+        };
+
+        for _ in 0..200 {
+            let a = rng.next_u32() as usize % n;
+            let b = rng.next_u32() as usize % n;
+            let pa = path_to_root(a);
+            let pb = path_to_root(b);
+            let l = *pa.iter().find(|v| pb.contains(v)).unwrap();
+            let mut path = pa
+                .iter()
+                .cloned()
+                .take_while(|&v| v != l)
+                .collect::<Vec<_>>();
+            path.push(l);
+            path.extend(
+                pb.iter()
+                    .cloned()
+                    .take_while(|&v| v != l)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .rev(),
+            );
+            let wd = path
+                .windows(2)
+                .map(|w| {
+                    edges
+                        .iter()
+                        .find(|e| e.0 == w[0] && e.1 == w[1] || e.0 == w[1] && e.1 == w[0])
+                        .unwrap()
+                        .2
+                })
+                .sum::<u64>();
+
+            assert_eq!(lca.lca(a, b), l);
+            assert_eq!(et.lca(a, b), l);
+            assert_eq!(lca.dist(a, b), path.len() - 1);
+            assert_eq!(et.dist(a, b), path.len() - 1);
+            assert_eq!(lca.weighted_dist(a, b, &wdepth), wd);
+            assert_eq!(et.weighted_dist(a, b, &wdepth), wd);
+
+            for k in 0..pa.len() + 1 {
+                assert_eq!(lca.kth_ancestor(a, k), pa.get(k).cloned());
+                assert_eq!(et.kth_ancestor(a, k), pa.get(k).cloned());
+            }
+            for k in 0..path.len() + 1 {
+                assert_eq!(lca.jump(a, b, k), path.get(k).cloned());
+                assert_eq!(et.jump(a, b, k), path.get(k).cloned());
+            }
+        }
+    }
+
+    // Construction must not overflow the stack on a long path
+    let n = 100_000;
+    let g = Graph::unweighted(n, &(1..n).map(|i| (i - 1, i)).collect::<Vec<_>>(), false);
+    assert_eq!(LCA::new(&g).lca(n - 1, n / 2), n / 2);
+    assert_eq!(EulerTourLCA::new(0, &g).lca(n - 1, n / 2), n / 2);
+}
+
+#[test]
+fn test_lca_fold() {
+    use rand::{Rng, SeedableRng, StdRng};
+
+    enum Max {}
+    impl Monoid for Max {
+        type T = u64;
+        fn id() -> u64 {
+            0
+        }
+        fn op(a: &u64, b: &u64) -> u64 {
+            *a.max(b)
+        }
+    }
+    // Non-commutative
+    enum Append {}
+    impl Monoid for Append {
+        type T = Vec<u64>;
+        fn id() -> Vec<u64> {
+            Vec::new()
+        }
+        fn op(a: &Vec<u64>, b: &Vec<u64>) -> Vec<u64> {
+            a.iter().chain(b.iter()).cloned().collect()
+        }
+    }
+
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+    let n = 200;
+    let edges = (1..n)
+        .map(|i| (rng.next_u32() as usize % i, i, rng.next_u64() % 1000))
+        .collect::<Vec<_>>();
+    let g = Graph::undirected(n, &edges);
+    let ga = Graph::undirected(
+        n,
+        &edges
+            .iter()
+            .map(|&(u, v, w)| (u, v, vec![w]))
+            .collect::<Vec<_>>(),
+    );
+    let fold_max: LCAFold<Max> = LCAFold::new(0, &g);
+    let fold_append: LCAFold<Append> = LCAFold::new(0, &ga);
+
+    for _ in 0..1000 {
+        let a = rng.next_u32() as usize % n;
+        let b = rng.next_u32() as usize % n;
+        let path = (0..fold_max.lca.dist(a, b) + 1)
+            .map(|k| fold_max.lca.jump(a, b, k).unwrap())
+            .collect::<Vec<_>>();
+        let ws = path
+            .windows(2)
+            .map(|w| {
+                edges
+                    .iter()
+                    .find(|e| e.0 == w[0] && e.1 == w[1] || e.0 == w[1] && e.1 == w[0])
+                    .unwrap()
+                    .2
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fold_max.path_fold(a, b),
+            ws.iter().cloned().max().unwrap_or(0)
+        );
+        assert_eq!(fold_append.path_fold(a, b), ws);
+    }
+}
+
+#[test]
+fn test_hld_path_ranges() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..20 {
+        let n = 100;
+        let mut g = vec![Vec::new(); n];
+        for i in 1..n {
+            let p = rng.next_u32() as usize % i;
+            g[p].push(i);
+            g[i].push(p);
+        }
+        let root = rng.next_u32() as usize % n;
+        let tree = Tree::from_neighbor_list(n, root, &g);
+        let hld = HeavyLightDecomposition::new(&tree);
+        let lca = LCA::with_root(root, &g);
+
+        let mut vertex = vec![0; n];
+        for v in 0..n {
+            vertex[hld.tin[v]] = v;
+        }
+
+        for v in 0..n {
+            let (l, r) = hld.subtree_range(v, false);
+            for &u in &vertex[l..r] {
+                assert_eq!(lca.lca(u, v), v);
+            }
+            assert_eq!(r - l, (0..n).filter(|&u| lca.lca(u, v) == v).count());
+        }
+
+        for _ in 0..100 {
+            let a = rng.next_u32() as usize % n;
+            let b = rng.next_u32() as usize % n;
+            let l = lca.lca(a, b);
+            assert_eq!(hld.lca(a, b), l);
+
+            let path = (0..lca.dist(a, b) + 1)
+                .map(|k| lca.jump(a, b, k).unwrap())
+                .collect::<Vec<_>>();
+            let from_ranges = |edge| {
+                let mut res: Vec<usize> = Vec::new();
+                for (l, r, rev) in hld.path_ranges(a, b, edge) {
+                    if rev {
+                        res.extend(vertex[l..r].iter().rev());
+                    } else {
+                        res.extend(vertex[l..r].iter());
+                    }
+                }
+                res
+            };
+            assert_eq!(from_ranges(false), path);
+            assert_eq!(
+                from_ranges(true),
+                path.into_iter().filter(|&v| v != l).collect::<Vec<_>>()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_lowlink_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    // Number of components without vertex `rv` and edge `re`
+    fn count(g: &Graph<()>, rv: usize, re: usize) -> usize {
+        let n = g.len();
+        let mut visited = vec![false; n];
+        let mut res = 0;
+        for s in (0..n).filter(|&s| s != rv) {
+            if visited[s] {
+                continue;
+            }
+            res += 1;
+            visited[s] = true;
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                for &(to, id) in g.adj(v) {
+                    if id != re && to != rv && !visited[to] {
+                        visited[to] = true;
+                        stack.push(to);
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    for _ in 0..200 {
+        let n = rng.next_u32() as usize % 12 + 1;
+        let m = rng.next_u32() as usize % 16;
+        let edges = (0..m)
+            .map(|_| (rng.next_u32() as usize % n, rng.next_u32() as usize % n))
+            .collect::<Vec<_>>();
+        let g = Graph::unweighted(n, &edges, false);
+        let ll = LowLink::new(&g);
+        let base = count(&g, !0, !0);
+
+        let ap = (0..n)
+            .filter(|&v| count(&g, v, !0) > base)
+            .collect::<Vec<_>>();
+        assert_eq!(ll.articulation_points, ap);
+
+        let mut br = (0..m)
+            .filter(|&e| count(&g, !0, e) > base)
+            .collect::<Vec<_>>();
+        let mut ll_br = ll.bridges.clone();
+        br.sort();
+        ll_br.sort();
+        assert_eq!(ll_br, br);
+
+        let without_bridges = Graph::unweighted(
+            n,
+            &(0..m)
+                .filter(|e| !br.contains(e))
+                .map(|e| edges[e])
+                .collect::<Vec<_>>(),
+            false,
+        );
+        let (c, cmp) = without_bridges.connected_components();
+        assert_eq!(ll.tecc_count, c);
+        for u in 0..n {
+            for v in 0..n {
+                assert_eq!(ll.tecc[u] == ll.tecc[v], cmp[u] == cmp[v]);
+            }
+        }
+        let bt = ll.bridge_tree(&g);
+        assert_eq!(bt.iter().map(|es| es.len()).sum::<usize>(), 2 * br.len());
+
+        // Every non-loop edge is in exactly one biconnected component
+        let mut cnt = vec![0; m];
+        for comp in &ll.bcc {
+            for &e in comp {
+                cnt[e] += 1;
+            }
+        }
+        for e in 0..m {
+            assert_eq!(cnt[e], (edges[e].0 != edges[e].1) as usize);
+        }
+        // Each biconnected component stays connected after removing any vertex
+        for comp in &ll.bcc {
+            let mut vs = comp
+                .iter()
+                .flat_map(|&e| vec![edges[e].0, edges[e].1])
+                .collect::<Vec<_>>();
+            vs.sort();
+            vs.dedup();
+            let sub = Graph::unweighted(
+                n,
+                &comp.iter().map(|&e| edges[e]).collect::<Vec<_>>(),
+                false,
+            );
+            let isolated = n - vs.len();
+            assert_eq!(count(&sub, !0, !0), isolated + 1);
+            if vs.len() >= 3 {
+                for &v in &vs {
+                    assert_eq!(count(&sub, v, !0), isolated + 1);
+                }
+            }
+        }
+        // Block-cut tree is a forest
+        let bct = ll.block_cut_tree(&g);
+        let bct_edges = bct.iter().map(|es| es.len()).sum::<usize>() / 2;
+        let bct_comp = Graph::from_adjacency(&bct).connected_components().0;
+        assert_eq!(bct_edges + bct_comp, bct.len());
+    }
+
+    assert_eq!(bridges(&vec![vec![1, 1], vec![0, 0]]), vec![]);
+    assert_eq!(
+        bridges(&vec![vec![1], vec![0], vec![3], vec![2]]),
+        vec![(0, 1), (2, 3)]
+    );
+    assert_eq!(
+        articulation_points(&vec![vec![], vec![2], vec![1, 3], vec![2]]),
+        vec![2]
+    );
+}
+
+#[test]
+fn test_centroid_decomposition() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..50 {
+        let n = rng.next_u32() as usize % 60 + 1;
+        let mut g = vec![Vec::new(); n];
+        for i in 1..n {
+            // Forests too
+            if rng.next_u32() % 10 != 0 {
+                let p = rng.next_u32() as usize % i;
+                g[p].push(i);
+                g[i].push(p);
+            }
+        }
+        let cd = CentroidDecomposition::new(&g);
+
+        let mut dist = vec![vec![None; n]; n];
+        for (s, ds) in dist.iter_mut().enumerate() {
+            ds[s] = Some(0);
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                for &to in &g[v] {
+                    if ds[to].is_none() {
+                        ds[to] = Some(ds[v].unwrap() + 1);
+                        stack.push(to);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(cd.order.len(), n);
+        for &c in &cd.order {
+            let comp = cd.component(&g, c);
+            assert_eq!(comp[0], (c, 0));
+            for &(v, d) in &comp {
+                assert_eq!(dist[c][v], Some(d));
+                // v is a descendant of c in the centroid tree
+                let mut u = v;
+                while u != c {
+                    u = cd.parent[u].unwrap();
+                }
+            }
+            let subtrees = cd.subtrees(&g, c);
+            assert!(subtrees.iter().all(|sub| 2 * sub.len() <= comp.len()));
+            assert_eq!(
+                subtrees.iter().map(|s| s.len()).sum::<usize>() + 1,
+                comp.len()
+            );
+            if let Some(p) = cd.parent[c] {
+                assert_eq!(cd.depth[c], cd.depth[p] + 1);
+            }
+        }
+
+        for k in 0..6 {
+            let brute = (0..n)
+                .map(|u| {
+                    (0..u)
+                        .filter(|&v| dist[u][v].map(|d| d <= k).unwrap_or(false))
+                        .count()
+                })
+                .sum::<usize>();
+            assert_eq!(count_paths_at_most(&g, k), brute);
+        }
+    }
+
+    let n = 200_000;
+    let g = (0..n)
+        .map(|i| {
+            let mut es = Vec::new();
+            if i > 0 {
+                es.push(i - 1);
+            }
+            if i + 1 < n {
+                es.push(i + 1);
+            }
+            es
+        })
+        .collect::<Vec<_>>();
+    let cd = CentroidDecomposition::new(&g);
+    assert!(cd.depth.iter().all(|&d| d < 18));
+    assert_eq!(count_paths_at_most(&g, 3), 3 * n - 6);
+}
+
+#[test]
+fn test_tree_toolkit() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..50 {
+        let n = rng.next_u32() as usize % 40 + 1;
+        let edges = (1..n)
+            .map(|i| (rng.next_u32() as usize % i, i, rng.next_u64() % 10))
+            .collect::<Vec<_>>();
+        let root = rng.next_u32() as usize % n;
+        let (tree, weight) = Tree::from_weighted_edges(n, root, &edges);
+        assert_eq!(Tree::from_parent(&tree.parent).root, root);
+
+        let mut g = vec![Vec::new(); n];
+        for &(u, v, w) in &edges {
+            g[u].push((v, w));
+            g[v].push((u, w));
+        }
+        let dist_from = |s: usize, weighted: bool| {
+            let mut dist = vec![None; n];
+            dist[s] = Some(0);
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                for &(to, w) in &g[v] {
+                    if dist[to].is_none() {
+                        dist[to] = Some(dist[v].unwrap() + if weighted { w } else { 1 });
+                        stack.push(to);
+                    }
+                }
+            }
+            dist.into_iter().map(Option::unwrap).collect::<Vec<u64>>()
+        };
+
+        let dist = dist_from(root, false);
+        for v in 0..n {
+            assert_eq!(tree.depth[v] as u64, dist[v]);
+            assert_eq!(tree.dfs_order[tree.tin[v]], v);
+            let sub = (0..n).filter(|&u| tree.is_ancestor(v, u)).count();
+            assert_eq!(tree.size[v], sub);
+            assert_eq!(tree.tout[v] - tree.tin[v], sub);
+            if let Some(p) = tree.parent[v] {
+                assert!(tree.tin[p] < tree.tin[v]);
+                assert!(tree.is_ancestor(p, v));
+                assert_eq!(g[p].iter().find(|e| e.0 == v).unwrap().1, weight[v]);
+            }
+        }
+        for w in tree.bfs_order.windows(2) {
+            assert!(tree.depth[w[0]] <= tree.depth[w[1]]);
+        }
+
+        let all = (0..n).map(|s| dist_from(s, true)).collect::<Vec<_>>();
+        let (d, path) = tree.weighted_diameter(&weight);
+        assert_eq!(d, all.iter().flatten().cloned().max().unwrap());
+        assert_eq!(all[path[0]][path[path.len() - 1]], d);
+        for w in path.windows(2) {
+            assert!(tree.parent[w[0]] == Some(w[1]) || tree.parent[w[1]] == Some(w[0]));
+        }
+
+        let ecc = (0..n)
+            .map(|s| dist_from(s, false).into_iter().max().unwrap())
+            .collect::<Vec<_>>();
+        let min = *ecc.iter().min().unwrap();
+        let mut centers = tree.centers();
+        centers.sort();
+        assert_eq!(
+            centers,
+            (0..n).filter(|&v| ecc[v] == min).collect::<Vec<_>>()
+        );
+        assert_eq!(tree.diameter().0 as u64, *ecc.iter().max().unwrap());
+    }
+}
+
+#[test]
+fn test_tree_hash() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+    let th = TreeHash::new(100, rng.next_u64());
+
+    fn canonical(tree: &Tree, v: usize) -> String {
+        let mut cs = tree.childs[v]
+            .iter()
+            .map(|&c| canonical(tree, c))
+            .collect::<Vec<_>>();
+        cs.sort();
+        format!("({})", cs.concat())
+    }
+
+    for _ in 0..100 {
+        let n = rng.next_u32() as usize % 12 + 1;
+        let random_tree = |rng: &mut StdRng| {
+            let parent = (0..n)
+                .map(|i| {
+                    if i == 0 {
+                        None
+                    } else {
+                        Some(rng.next_u32() as usize % i)
+                    }
+                })
+                .collect::<Vec<_>>();
+            Tree::from_parent(&parent)
+        };
+        let a = random_tree(&mut rng);
+        let b = random_tree(&mut rng);
+
+        let ha = th.rooted(&a);
+        let hb = th.rooted(&b);
+        let ca = (0..n).map(|v| canonical(&a, v)).collect::<Vec<_>>();
+        let cb = (0..n).map(|v| canonical(&b, v)).collect::<Vec<_>>();
+        for u in 0..n {
+            for v in 0..n {
+                assert_eq!(ha[u] == hb[v], ca[u] == cb[v]);
+            }
+        }
+
+        // Relabel and reroot b
+        let mut perm = (0..n).collect::<Vec<_>>();
+        rng.shuffle(&mut perm);
+        let mut g = vec![Vec::new(); n];
+        for (v, p) in b.parent.iter().enumerate() {
+            if let Some(p) = *p {
+                g[perm[v]].push(perm[p]);
+                g[perm[p]].push(perm[v]);
+            }
+        }
+        let r = perm[b.root];
+        assert_eq!(
+            th.rooted(&Tree::from_neighbor_list(n, r, &g))[r],
+            hb[b.root]
+        );
+        let c = Tree::from_neighbor_list(n, rng.next_u32() as usize % n, &g);
+        assert_eq!(th.unrooted(&c), th.unrooted(&b));
+
+        let unrooted_canonical = |t: &Tree| {
+            let mut g = vec![Vec::new(); n];
+            for (v, p) in t.parent.iter().enumerate() {
+                if let Some(p) = *p {
+                    g[v].push(p);
+                    g[p].push(v);
+                }
+            }
+            (0..n)
+                .map(|r| canonical(&Tree::from_neighbor_list(n, r, &g), r))
+                .min()
+                .unwrap()
+        };
+        assert_eq!(
+            th.unrooted(&a) == th.unrooted(&b),
+            unrooted_canonical(&a) == unrooted_canonical(&b)
+        );
+    }
+}
+
+#[test]
+fn test_virtual_tree() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..100 {
+        let n = rng.next_u32() as usize % 50 + 1;
+        let mut g = vec![Vec::new(); n];
+        for i in 1..n {
+            let p = rng.next_u32() as usize % i;
+            g[p].push(i);
+            g[i].push(p);
+        }
+        let root = rng.next_u32() as usize % n;
+        let tree = Tree::from_neighbor_list(n, root, &g);
+        let lca = LCA::with_root(root, &g);
+        let et = EulerTourLCA::new(root, &g);
+
+        let k = rng.next_u32() as usize % 8 + 1;
+        let vs = (0..k)
+            .map(|_| rng.next_u32() as usize % n)
+            .collect::<Vec<_>>();
+
+        // Closed under LCA
+        let mut expected = vs.clone();
+        for &a in &vs {
+            for &b in &vs {
+                expected.push(lca.lca(a, b));
+            }
+        }
+        expected.sort();
+        expected.dedup();
+
+        let (nodes, edges) = virtual_tree(&tree, |a, b| lca.lca(a, b), &tree.depth, &vs);
+        assert_eq!(
+            (nodes.clone(), edges.clone()),
+            virtual_tree(&tree, |a, b| et.lca(a, b), &tree.depth, &vs)
+        );
+        assert!(nodes.len() < 2 * k);
+        let mut sorted = nodes.clone();
+        sorted.sort();
+        assert_eq!(sorted, expected);
+        assert_eq!(edges.len(), nodes.len() - 1);
+        assert!(nodes.iter().all(|&v| tree.is_ancestor(nodes[0], v)));
+
+        for &(p, c, d) in &edges {
+            // p is the nearest proper ancestor of c among the nodes
+            let mut u = tree.parent[c].unwrap();
+            while !nodes.contains(&u) {
+                u = tree.parent[u].unwrap();
+            }
+            assert_eq!(u, p);
+            assert_eq!(d, lca.dist(p, c));
+        }
+
+        // Weighted lengths are sums of the weights on the original path
+        let weight = (0..n).map(|_| rng.next_u64() % 100).collect::<Vec<_>>();
+        let wdepth = tree.weighted_depth(&weight);
+        let (wnodes, wedges) = virtual_tree(&tree, |a, b| lca.lca(a, b), &wdepth, &vs);
+        assert_eq!(wnodes, nodes);
+        for (&(p, c, w), &(p2, c2, _)) in wedges.iter().zip(edges.iter()) {
+            assert_eq!((p, c), (p2, c2));
+            let mut sum = 0;
+            let mut v = c;
+            while v != p {
+                sum += weight[v];
+                v = tree.parent[v].unwrap();
+            }
+            assert_eq!(w, sum);
+        }
+    }
+}
+
+#[test]
+fn test_dominator_tree_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    fn reachable(g: &[Vec<usize>], root: usize, removed: usize) -> Vec<bool> {
+        let mut res = vec![false; g.len()];
+        if root == removed {
+            return res;
+        }
+        res[root] = true;
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            for &to in &g[v] {
+                if to != removed && !res[to] {
+                    res[to] = true;
+                    stack.push(to);
+                }
+            }
+        }
+        res
+    }
+
+    for _ in 0..200 {
+        let n = rng.next_u32() as usize % 12 + 1;
+        let m = rng.next_u32() as usize % (2 * n + 1);
+        let mut g = vec![Vec::new(); n];
+        for _ in 0..m {
+            let u = rng.next_u32() as usize % n;
+            let v = rng.next_u32() as usize % n;
+            g[u].push(v);
+        }
+        let root = rng.next_u32() as usize % n;
+        let dt = DominatorTree::new(root, &g);
+        let tree = dt.to_tree();
+
+        let reach = reachable(&g, root, !0);
+        for v in 0..n {
+            if !reach[v] {
+                assert_eq!(dt.idom[v], None);
+                assert!((0..n).all(|u| !tree.is_ancestor(u, v) && !tree.is_ancestor(v, u)));
+                continue;
+            }
+            if v == root {
+                assert_eq!(dt.idom[v], None);
+                continue;
+            }
+            // Strict dominators of v are exactly its proper ancestors
+            for (u, &ru) in reach.iter().enumerate() {
+                let dominates = u != v && ru && !reachable(&g, root, u)[v];
+                assert_eq!(dominates, u != v && tree.is_ancestor(u, v));
+            }
+        }
+    }
+
+    // Long path with back edges
+    let n = 200_000;
+    let g = (0..n)
+        .map(|i| {
+            let mut es = vec![];
+            if i + 1 < n {
+                es.push(i + 1);
+            }
+            if i % 3 == 2 {
+                es.push(i - 2);
+            }
+            es
+        })
+        .collect::<Vec<_>>();
+    let dt = DominatorTree::new(0, &g);
+    assert!((1..n).all(|v| dt.idom[v] == Some(v - 1)));
+}
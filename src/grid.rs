@@ -0,0 +1,284 @@
+use crate::misc::{adjacent, DIR4, DIR8, KNIGHT};
+use cargo_snippet::snippet;
+use std::collections::VecDeque;
+
+#[snippet("Grid")]
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Moves {
+    Four,
+    Eight,
+    Knight,
+}
+
+#[snippet("Grid")]
+#[snippet(include = "adjacent")]
+#[derive(Clone, Debug)]
+/// H * W grid. A cell is identified by its index `r * w + c`.
+pub struct Grid<T> {
+    pub h: usize,
+    pub w: usize,
+    pub cells: Vec<T>,
+    pub wall: Vec<bool>,
+    pub moves: Moves,
+}
+
+#[snippet("Grid")]
+impl Grid<char> {
+    #[allow(dead_code)]
+    /// `lines` are rows read by `util::chars()`. Cells in `walls` are impassable.
+    pub fn from_chars(lines: &[Vec<char>], walls: &[char]) -> Grid<char> {
+        let w = lines.first().map(|l| l.len()).unwrap_or(0);
+        let cells = lines.concat();
+        assert_eq!(cells.len(), lines.len() * w);
+        Grid::new(lines.len(), w, cells, |c| walls.contains(c))
+    }
+}
+
+#[snippet("Grid")]
+#[allow(dead_code)]
+impl<T> Grid<T> {
+    pub fn new<F: Fn(&T) -> bool>(h: usize, w: usize, cells: Vec<T>, is_wall: F) -> Grid<T> {
+        let wall = cells.iter().map(is_wall).collect();
+        Grid {
+            h,
+            w,
+            cells,
+            wall,
+            moves: Moves::Four,
+        }
+    }
+
+    pub fn with_moves(mut self, moves: Moves) -> Grid<T> {
+        self.moves = moves;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn id(&self, r: usize, c: usize) -> usize {
+        r * self.w + c
+    }
+
+    /// (row, col)
+    pub fn pos(&self, id: usize) -> (usize, usize) {
+        (id / self.w, id % self.w)
+    }
+
+    /// Indices of the cells satisfying `f`
+    pub fn positions<F: Fn(&T) -> bool>(&self, f: F) -> Vec<usize> {
+        (0..self.len()).filter(|&i| f(&self.cells[i])).collect()
+    }
+
+    /// Neighbors of `id` inside the grid, including walls
+    pub fn adjacent(&self, id: usize) -> impl Iterator<Item = usize> {
+        let dirs: &'static [(isize, isize)] = match self.moves {
+            Moves::Four => &DIR4,
+            Moves::Eight => &DIR8,
+            Moves::Knight => &KNIGHT,
+        };
+        let (r, c) = self.pos(id);
+        let w = self.w;
+        adjacent(r, c, self.h, w, dirs).map(move |(r, c)| r * w + c)
+    }
+
+    /// Passable neighbors of `id`
+    pub fn neighbors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.adjacent(id).filter(move |&to| !self.wall[to])
+    }
+
+    /// Number of moves from the nearest source. Walls are not entered.
+    pub fn bfs(&self, sources: &[usize]) -> Vec<Option<usize>> {
+        let mut dist = vec![None; self.len()];
+        let mut queue = VecDeque::new();
+        for &s in sources {
+            if dist[s].is_none() {
+                dist[s] = Some(0);
+                queue.push_back(s);
+            }
+        }
+        while let Some(v) = queue.pop_front() {
+            let d = dist[v].unwrap() + 1;
+            for to in self.neighbors(v) {
+                if dist[to].is_none() {
+                    dist[to] = Some(d);
+                    queue.push_back(to);
+                }
+            }
+        }
+        dist
+    }
+
+    /// 0-1 BFS over all adjacent cells including walls.
+    /// `cost(from, to)` must be `Some(0)`, `Some(1)` or `None` if the move is not allowed.
+    pub fn bfs01<F: Fn(usize, usize) -> Option<usize>>(
+        &self,
+        sources: &[usize],
+        cost: F,
+    ) -> Vec<Option<usize>> {
+        let mut dist: Vec<Option<usize>> = vec![None; self.len()];
+        let mut queue = VecDeque::new();
+        for &s in sources {
+            dist[s] = Some(0);
+            queue.push_back(s);
+        }
+        while let Some(v) = queue.pop_front() {
+            let dv = dist[v].unwrap();
+            for to in self.adjacent(v) {
+                if let Some(c) = cost(v, to) {
+                    let d = dv + c;
+                    if dist[to].map(|x| d < x).unwrap_or(true) {
+                        dist[to] = Some(d);
+                        if c == 0 {
+                            queue.push_front(to);
+                        } else {
+                            queue.push_back(to);
+                        }
+                    }
+                }
+            }
+        }
+        dist
+    }
+
+    /// Returns (number of components, component id of each passable cell)
+    pub fn components(&self) -> (usize, Vec<Option<usize>>) {
+        let mut cmp = vec![None; self.len()];
+        let mut count = 0;
+        for s in 0..self.len() {
+            if self.wall[s] || cmp[s].is_some() {
+                continue;
+            }
+            cmp[s] = Some(count);
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                for to in self.neighbors(v) {
+                    if cmp[to].is_none() {
+                        cmp[to] = Some(count);
+                        stack.push(to);
+                    }
+                }
+            }
+            count += 1;
+        }
+        (count, cmp)
+    }
+}
+
+#[snippet("Grid")]
+impl<T> std::ops::Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+    fn index(&self, (r, c): (usize, usize)) -> &T {
+        &self.cells[r * self.w + c]
+    }
+}
+
+#[test]
+fn test_grid_bfs() {
+    let lines = ["S..#", ".#.#", "...G"]
+        .iter()
+        .map(|l| l.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let grid = Grid::from_chars(&lines, &['#']);
+    let s = grid.positions(|&c| c == 'S')[0];
+    let g = grid.positions(|&c| c == 'G')[0];
+    assert_eq!(grid.pos(g), (2, 3));
+    assert_eq!(grid[(2, 3)], 'G');
+
+    assert_eq!(grid.bfs(&[s])[g], Some(5));
+    assert_eq!(grid.bfs(&[s])[grid.id(0, 3)], None);
+    assert_eq!(grid.components().0, 1);
+    // Breaking walls costs 1
+    let broken = grid.bfs01(&[s], |_, to| Some(grid.wall[to] as usize));
+    assert_eq!(broken[g], Some(0));
+    assert_eq!(broken[grid.id(0, 3)], Some(1));
+
+    let grid = grid.with_moves(Moves::Eight);
+    assert_eq!(grid.bfs(&[s])[g], Some(3));
+    let grid = grid.with_moves(Moves::Knight);
+    assert_eq!(grid.bfs(&[s])[grid.id(1, 2)], Some(1));
+}
+
+#[test]
+fn test_grid_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..100 {
+        let h = rng.next_u32() as usize % 8 + 1;
+        let w = rng.next_u32() as usize % 8 + 1;
+        let lines = (0..h)
+            .map(|_| {
+                (0..w)
+                    .map(|_| if rng.next_u32() % 3 == 0 { '#' } else { '.' })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let kind = [Moves::Four, Moves::Eight, Moves::Knight][rng.next_u32() as usize % 3];
+        let grid = Grid::from_chars(&lines, &['#']).with_moves(kind);
+        let n = h * w;
+        let sources = (0..rng.next_u32() % 3 + 1)
+            .map(|_| rng.next_u32() as usize % n)
+            .filter(|&s| !grid.wall[s])
+            .collect::<Vec<_>>();
+
+        // Relax until nothing changes
+        let relax = |cost: &dyn Fn(usize, usize) -> Option<usize>| {
+            let mut dist = vec![None; n];
+            for &s in &sources {
+                dist[s] = Some(0);
+            }
+            loop {
+                let mut updated = false;
+                for v in 0..n {
+                    if let Some(dv) = dist[v] {
+                        for to in grid.adjacent(v) {
+                            if let Some(c) = cost(v, to) {
+                                if dist[to].map(|x| dv + c < x).unwrap_or(true) {
+                                    dist[to] = Some(dv + c);
+                                    updated = true;
+                                }
+                            }
+                        }
+                    }
+                }
+                if !updated {
+                    return dist;
+                }
+            }
+        };
+
+        let walk = |_: usize, to: usize| if grid.wall[to] { None } else { Some(1) };
+        assert_eq!(grid.bfs(&sources), relax(&walk));
+        let weighted = |v: usize, to: usize| Some((v + to) % 2);
+        assert_eq!(grid.bfs01(&sources, weighted), relax(&weighted));
+
+        let (count, cmp) = grid.components();
+        let mut uft = crate::uft::UFT::new(n);
+        for v in (0..n).filter(|&v| !grid.wall[v]) {
+            for to in grid.neighbors(v) {
+                uft.merge(v, to);
+            }
+        }
+        let mut roots = (0..n)
+            .filter(|&v| !grid.wall[v])
+            .map(|v| uft.root(v))
+            .collect::<Vec<_>>();
+        roots.sort();
+        roots.dedup();
+        assert_eq!(roots.len(), count);
+        for u in 0..n {
+            for v in 0..n {
+                if !grid.wall[u] && !grid.wall[v] {
+                    assert_eq!(cmp[u] == cmp[v], uft.root(u) == uft.root(v));
+                }
+            }
+        }
+    }
+}
@@ -8,9 +8,12 @@ pub mod binary_search;
 pub mod bit;
 pub mod bitset;
 pub mod bucket;
+pub mod eulerian_path;
 pub mod flow;
+pub mod functional_graph;
 pub mod geometry;
 pub mod graph;
+pub mod grid;
 pub mod interval_heap;
 pub mod lexical_permutation;
 pub mod lis;
@@ -19,13 +22,19 @@ pub mod math;
 pub mod misc;
 pub mod modulo;
 pub mod monoid;
+pub mod mst;
 pub mod pcg;
 pub mod rc_list;
+pub mod rerooting;
 pub mod rolling_hash;
 pub mod seg_lazy;
 pub mod segtree;
+pub mod shortest_path;
 pub mod skew_heap;
+pub mod small_graph;
 pub mod template;
+pub mod topological_sort;
 pub mod total;
+pub mod two_sat;
 pub mod uft;
 pub mod util;
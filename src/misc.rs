@@ -31,40 +31,49 @@ impl<T: Ord, U> Ord for CmpBy<T, U> {
     }
 }
 
-#[snippet("adjacent4")]
+#[snippet("adjacent")]
 #[allow(dead_code)]
-fn adjacent4(x: usize, y: usize, sx: usize, sy: usize) -> impl Iterator<Item = (usize, usize)> {
-    [(-1, 0), (1, 0), (0, -1), (0, 1)]
-        .iter()
-        .filter_map(move |&(dx, dy)| {
-            let nx = x as isize + dx;
-            let ny = y as isize + dy;
-            if nx >= 0 && nx < sx as isize && ny >= 0 && ny < sy as isize {
-                Some((nx as usize, ny as usize))
-            } else {
-                None
-            }
-        })
-}
+pub const DIR4: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
 
-#[snippet("adjacent8")]
+#[snippet("adjacent")]
 #[allow(dead_code)]
-fn adjacent8(x: usize, y: usize, sx: usize, sy: usize) -> impl Iterator<Item = (usize, usize)> {
-    [
-        (-1, 0),
-        (1, 0),
-        (0, -1),
-        (0, 1),
-        (-1, -1),
-        (-1, 1),
-        (1, -1),
-        (1, 1),
-    ]
-    .iter()
-    .filter_map(move |&(dx, dy)| {
+pub const DIR8: [(isize, isize); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+];
+
+#[snippet("adjacent")]
+#[allow(dead_code)]
+pub const KNIGHT: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+#[snippet("adjacent")]
+#[allow(dead_code)]
+/// Cells (x + dx, y + dy) inside [0, sx) * [0, sy)
+pub fn adjacent<'a>(
+    x: usize,
+    y: usize,
+    sx: usize,
+    sy: usize,
+    dirs: &'a [(isize, isize)],
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    dirs.iter().filter_map(move |&(dx, dy)| {
         let nx = x as isize + dx;
         let ny = y as isize + dy;
-
         if nx >= 0 && nx < sx as isize && ny >= 0 && ny < sy as isize {
             Some((nx as usize, ny as usize))
         } else {
@@ -73,6 +82,20 @@ fn adjacent8(x: usize, y: usize, sx: usize, sy: usize) -> impl Iterator<Item = (
     })
 }
 
+#[snippet("adjacent4")]
+#[snippet(include = "adjacent")]
+#[allow(dead_code)]
+pub fn adjacent4(x: usize, y: usize, sx: usize, sy: usize) -> impl Iterator<Item = (usize, usize)> {
+    adjacent(x, y, sx, sy, &DIR4)
+}
+
+#[snippet("adjacent8")]
+#[snippet(include = "adjacent")]
+#[allow(dead_code)]
+pub fn adjacent8(x: usize, y: usize, sx: usize, sy: usize) -> impl Iterator<Item = (usize, usize)> {
+    adjacent(x, y, sx, sy, &DIR8)
+}
+
 #[test]
 fn test_adjacent4() {
     let mut a4 = adjacent4(1, 1, 3, 3).collect::<Vec<_>>();
@@ -115,3 +138,13 @@ fn test_adjacent8() {
 
     assert_eq!(&a8, &expected);
 }
+
+#[test]
+fn test_adjacent_knight() {
+    let mut k = adjacent(0, 1, 4, 4, &KNIGHT).collect::<Vec<_>>();
+    let mut expected = [(1, 3), (2, 0), (2, 2)];
+    k.sort();
+    expected.sort();
+
+    assert_eq!(&k, &expected);
+}
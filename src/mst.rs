@@ -0,0 +1,319 @@
+use crate::bit::BIT;
+use crate::graph::Graph;
+use crate::monoid::Monoid;
+use crate::shortest_path::Cost;
+use crate::uft::UFT;
+use cargo_snippet::snippet;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[snippet("SpanningTree")]
+#[snippet(include = "Cost")]
+#[derive(Clone, Debug)]
+/// Minimum spanning forest
+pub struct SpanningTree<C> {
+    pub weight: C,
+    /// Chosen edges (u, v, cost)
+    pub edges: Vec<(usize, usize, C)>,
+    /// Edge ids of the chosen edges in the input graph. Empty if edges are implicit
+    pub ids: Vec<usize>,
+}
+
+#[snippet("SpanningTree")]
+impl<C: Cost> SpanningTree<C> {
+    #[allow(dead_code)]
+    /// Neighbor list of the chosen edges over `n` vertices, for `Tree::from_neighbor_list`
+    pub fn neighbor_list(&self, n: usize) -> Vec<Vec<usize>> {
+        let mut res = vec![Vec::new(); n];
+        for &(u, v, _) in &self.edges {
+            res[u].push(v);
+            res[v].push(u);
+        }
+        res
+    }
+}
+
+#[snippet("kruskal")]
+#[snippet(include = "SpanningTree")]
+#[snippet(include = "Graph")]
+#[snippet(include = "UFT")]
+#[allow(dead_code)]
+/// Kruskal's algorithm. O(E log E)
+pub fn kruskal<C: Cost>(g: &Graph<C>) -> SpanningTree<C> {
+    let mut ids = (0..g.edges.len()).collect::<Vec<_>>();
+    ids.sort_by_key(|&id| g.edges[id].2);
+
+    let mut uft = UFT::new(g.len());
+    let mut weight = C::zero();
+    let mut chosen = Vec::new();
+    for id in ids {
+        let (u, v, w) = g.edges[id];
+        if uft.root(u) != uft.root(v) {
+            uft.merge(u, v);
+            weight = weight.add(w);
+            chosen.push(id);
+        }
+    }
+
+    SpanningTree {
+        weight,
+        edges: chosen.iter().map(|&id| g.edges[id]).collect(),
+        ids: chosen,
+    }
+}
+
+#[snippet("prim")]
+#[snippet(include = "SpanningTree")]
+#[snippet(include = "Graph")]
+#[allow(dead_code)]
+/// Prim's algorithm with a binary heap. O(E log V)
+pub fn prim<C: Cost>(g: &Graph<C>) -> SpanningTree<C> {
+    let n = g.len();
+    let mut used = vec![false; n];
+    let mut weight = C::zero();
+    let mut chosen = Vec::new();
+    let mut heap = BinaryHeap::new();
+
+    for s in 0..n {
+        if used[s] {
+            continue;
+        }
+        used[s] = true;
+        for &(to, id) in g.adj(s) {
+            heap.push(Reverse((*g.weight(id), to, id)));
+        }
+
+        while let Some(Reverse((w, v, id))) = heap.pop() {
+            if used[v] {
+                continue;
+            }
+            used[v] = true;
+            weight = weight.add(w);
+            chosen.push(id);
+            for &(to, id) in g.adj(v) {
+                if !used[to] {
+                    heap.push(Reverse((*g.weight(id), to, id)));
+                }
+            }
+        }
+    }
+
+    SpanningTree {
+        weight,
+        edges: chosen.iter().map(|&id| g.edges[id]).collect(),
+        ids: chosen,
+    }
+}
+
+#[snippet("boruvka")]
+#[snippet(include = "SpanningTree")]
+#[snippet(include = "UFT")]
+#[allow(dead_code)]
+/// Borůvka's algorithm for graphs whose edges are given implicitly.
+/// `cheapest(cmp)` receives the component id of each vertex and must return,
+/// for each vertex, the cheapest edge (cost, to) to a vertex of another component.
+/// O(log V) calls of `cheapest`.
+pub fn boruvka<C: Cost, F: FnMut(&[usize]) -> Vec<Option<(C, usize)>>>(
+    n: usize,
+    mut cheapest: F,
+) -> SpanningTree<C> {
+    let mut uft = UFT::new(n);
+    let mut weight = C::zero();
+    let mut edges = Vec::new();
+
+    loop {
+        let cmp = (0..n).map(|v| uft.root(v)).collect::<Vec<_>>();
+        let mut best: Vec<Option<(C, usize, usize)>> = vec![None; n];
+        for (v, e) in cheapest(&cmp).into_iter().enumerate() {
+            if let Some((c, to)) = e {
+                let e = (c, v.min(to), v.max(to));
+                let b = &mut best[cmp[v]];
+                if b.map(|b| e < b).unwrap_or(true) {
+                    *b = Some(e);
+                }
+            }
+        }
+
+        let mut updated = false;
+        for (c, u, v) in best.into_iter().flatten() {
+            if uft.root(u) != uft.root(v) {
+                uft.merge(u, v);
+                weight = weight.add(c);
+                edges.push((u, v, c));
+                updated = true;
+            }
+        }
+        if !updated {
+            break;
+        }
+    }
+
+    SpanningTree {
+        weight,
+        edges,
+        ids: Vec::new(),
+    }
+}
+
+#[snippet("manhattan_mst")]
+#[snippet(include = "SpanningTree")]
+#[snippet(include = "BIT")]
+#[snippet(include = "UFT")]
+#[allow(dead_code)]
+/// Minimum spanning tree of points by Manhattan distance. O(N log N)
+pub fn manhattan_mst(points: &[(i64, i64)]) -> SpanningTree<u64> {
+    enum MinIndex {}
+    impl Monoid for MinIndex {
+        type T = (i64, usize);
+        fn id() -> Self::T {
+            (i64::MAX, !0)
+        }
+        fn op(a: &Self::T, b: &Self::T) -> Self::T {
+            *a.min(b)
+        }
+    }
+
+    let n = points.len();
+    let mut ps = points.to_vec();
+    let mut candidates = Vec::new();
+
+    for dir in 0..4 {
+        if dir % 2 == 1 {
+            for p in &mut ps {
+                *p = (p.1, p.0);
+            }
+        } else if dir == 2 {
+            for p in &mut ps {
+                p.0 = -p.0;
+            }
+        }
+
+        let mut ids = (0..n).collect::<Vec<_>>();
+        ids.sort_by_key(|&i| ps[i]);
+        let mut keys = ps.iter().map(|&(x, y)| y - x).collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        let m = keys.len();
+
+        // For each point, find the point with the smallest x + y among
+        // points with larger x and larger y - x.
+        let mut bit: BIT<MinIndex> = BIT::new(m);
+        for &i in ids.iter().rev() {
+            let (x, y) = ps[i];
+            let k = m - keys.binary_search(&(y - x)).unwrap();
+            let (_, j) = bit.sum(k);
+            if j != !0 {
+                let (xj, yj) = points[j];
+                let (xi, yi) = points[i];
+                candidates.push(((xi - xj).abs() + (yi - yj).abs(), i, j));
+            }
+            bit.add(k, &(x + y, i));
+        }
+    }
+
+    candidates.sort();
+    let mut uft = UFT::new(n);
+    let mut weight = 0;
+    let mut edges = Vec::new();
+    for (d, u, v) in candidates {
+        if uft.root(u) != uft.root(v) {
+            uft.merge(u, v);
+            weight += d as u64;
+            edges.push((u, v, d as u64));
+        }
+    }
+
+    SpanningTree {
+        weight,
+        edges,
+        ids: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+fn random_weighted_graph<R: rand::Rng>(rng: &mut R, n: usize, m: usize) -> Graph<u64> {
+    let edges = (0..m)
+        .map(|_| {
+            (
+                rng.next_u32() as usize % n,
+                rng.next_u32() as usize % n,
+                rng.next_u64() % 100,
+            )
+        })
+        .collect::<Vec<_>>();
+    Graph::undirected(n, &edges)
+}
+
+#[test]
+fn test_mst_kruskal_prim_boruvka() {
+    use crate::graph::Tree;
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..50 {
+        let n = 40;
+        let g = random_weighted_graph(&mut rng, n, 100);
+        let k = kruskal(&g);
+        let p = prim(&g);
+        let b = boruvka(n, |cmp| {
+            (0..n)
+                .map(|v| {
+                    g.adj(v)
+                        .iter()
+                        .filter(|&&(to, _)| cmp[to] != cmp[v])
+                        .map(|&(to, id)| (*g.weight(id), to))
+                        .min()
+                })
+                .collect()
+        });
+
+        let (cnt, _) = g.connected_components();
+        assert_eq!(k.edges.len(), n - cnt);
+        assert_eq!(p.edges.len(), n - cnt);
+        assert_eq!(b.edges.len(), n - cnt);
+        assert_eq!(k.weight, p.weight);
+        assert_eq!(k.weight, b.weight);
+        assert_eq!(k.weight, k.ids.iter().map(|&id| g.edges[id].2).sum());
+        assert_eq!(k.weight, k.edges.iter().map(|e| e.2).sum());
+
+        if cnt == 1 {
+            for t in &[k, p, b] {
+                let tree = Tree::from_neighbor_list(n, 0, &t.neighbor_list(n));
+                assert_eq!(tree.parent.iter().filter(|p| p.is_none()).count(), 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_manhattan_mst() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..50 {
+        let n = 30;
+        let points = (0..n)
+            .map(|_| {
+                (
+                    (rng.next_u32() % 41) as i64 - 20,
+                    (rng.next_u32() % 41) as i64 - 20,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut edges = Vec::new();
+        for i in 0..n {
+            for j in 0..i {
+                let (xi, yi) = points[i];
+                let (xj, yj) = points[j];
+                edges.push((i, j, ((xi - xj).abs() + (yi - yj).abs()) as u64));
+            }
+        }
+
+        let mst = manhattan_mst(&points);
+        assert_eq!(mst.edges.len(), n - 1);
+        assert_eq!(mst.weight, kruskal(&Graph::undirected(n, &edges)).weight);
+        let tree = crate::graph::Tree::from_neighbor_list(n, 0, &mst.neighbor_list(n));
+        assert_eq!(tree.size[0], n);
+    }
+}
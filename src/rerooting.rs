@@ -0,0 +1,209 @@
+use crate::graph::Adjacency;
+use cargo_snippet::snippet;
+
+#[snippet("Rerooting")]
+#[snippet(include = "Adjacency")]
+/// Tree DP computed for every root.
+/// The value of a subtree rooted at v is
+/// `add_root(merge(edge(child value, c, v) for each child c), v)`.
+pub trait Rerooting {
+    type T: Clone;
+    /// Identity of `merge`
+    fn identity(&self) -> Self::T;
+    /// Must be associative and commutative
+    fn merge(&self, a: &Self::T, b: &Self::T) -> Self::T;
+    fn add_root(&self, x: &Self::T, v: usize) -> Self::T;
+    /// Transform the value of the subtree rooted at `child` when it hangs from `parent`
+    fn edge(&self, x: &Self::T, child: usize, parent: usize) -> Self::T;
+
+    /// Value of the whole tree rooted at each vertex. O(N) and no recursion.
+    fn solve<G: Adjacency + ?Sized>(&self, g: &G) -> Vec<Self::T> {
+        let n = g.vertices();
+        let mut parent = vec![!0; n];
+        let mut order = Vec::with_capacity(n);
+        let mut visited = vec![false; n];
+        for r in 0..n {
+            if visited[r] {
+                continue;
+            }
+            visited[r] = true;
+            let head = order.len();
+            order.push(r);
+            for k in head.. {
+                if k == order.len() {
+                    break;
+                }
+                let v = order[k];
+                for i in 0..g.degree(v) {
+                    let to = g.neighbor(v, i);
+                    if !visited[to] {
+                        visited[to] = true;
+                        parent[to] = v;
+                        order.push(to);
+                    }
+                }
+            }
+        }
+
+        // Bottom-up
+        let mut acc = vec![self.identity(); n];
+        let mut down = vec![self.identity(); n];
+        for &v in order.iter().rev() {
+            down[v] = self.add_root(&acc[v], v);
+            let p = parent[v];
+            if p != !0 {
+                acc[p] = self.merge(&acc[p], &self.edge(&down[v], v, p));
+            }
+        }
+
+        // Top-down. up[v] is the value of the parent side hanging from v
+        let mut up = vec![self.identity(); n];
+        let mut res = vec![self.identity(); n];
+        for &v in &order {
+            let d = g.degree(v);
+            let vals = (0..d)
+                .map(|i| {
+                    let to = g.neighbor(v, i);
+                    if to == parent[v] {
+                        up[v].clone()
+                    } else {
+                        self.edge(&down[to], to, v)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut suffix = vec![self.identity(); d + 1];
+            for i in (0..d).rev() {
+                suffix[i] = self.merge(&vals[i], &suffix[i + 1]);
+            }
+            res[v] = self.add_root(&suffix[0], v);
+
+            let mut prefix = self.identity();
+            for i in 0..d {
+                let to = g.neighbor(v, i);
+                if to != parent[v] {
+                    let rest = self.add_root(&self.merge(&prefix, &suffix[i + 1]), v);
+                    up[to] = self.edge(&rest, v, to);
+                }
+                prefix = self.merge(&prefix, &vals[i]);
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+/// (sum of distances, number of vertices)
+struct DistanceSum;
+
+#[cfg(test)]
+impl Rerooting for DistanceSum {
+    type T = (u64, u64);
+    fn identity(&self) -> (u64, u64) {
+        (0, 0)
+    }
+    fn merge(&self, a: &(u64, u64), b: &(u64, u64)) -> (u64, u64) {
+        (a.0 + b.0, a.1 + b.1)
+    }
+    fn add_root(&self, x: &(u64, u64), _v: usize) -> (u64, u64) {
+        (x.0, x.1 + 1)
+    }
+    fn edge(&self, x: &(u64, u64), _child: usize, _parent: usize) -> (u64, u64) {
+        (x.0 + x.1, x.1)
+    }
+}
+
+#[cfg(test)]
+/// Farthest distance with edge weights
+struct Eccentricity {
+    weight: std::collections::HashMap<(usize, usize), u64>,
+}
+
+#[cfg(test)]
+impl Rerooting for Eccentricity {
+    type T = u64;
+    fn identity(&self) -> u64 {
+        0
+    }
+    fn merge(&self, a: &u64, b: &u64) -> u64 {
+        *a.max(b)
+    }
+    fn add_root(&self, x: &u64, _v: usize) -> u64 {
+        *x
+    }
+    fn edge(&self, x: &u64, child: usize, parent: usize) -> u64 {
+        x + self.weight[&(child, parent)]
+    }
+}
+
+#[test]
+fn test_rerooting_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..20 {
+        let n = 50;
+        let mut g = vec![Vec::new(); n];
+        let mut weight = std::collections::HashMap::new();
+        for i in 1..n {
+            let p = rng.next_u32() as usize % i;
+            let w = rng.next_u64() % 100;
+            g[p].push(i);
+            g[i].push(p);
+            weight.insert((p, i), w);
+            weight.insert((i, p), w);
+        }
+
+        let dist_sum = DistanceSum.solve(&g);
+        let root = rng.next_u32() as usize % n;
+        assert_eq!(
+            DistanceSum.solve(&crate::graph::Tree::from_neighbor_list(n, root, &g)),
+            dist_sum
+        );
+        let ecc = Eccentricity {
+            weight: weight.clone(),
+        }
+        .solve(&g);
+
+        for s in 0..n {
+            let mut dist = vec![None; n];
+            let mut wdist = vec![0; n];
+            dist[s] = Some(0);
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                for &to in &g[v] {
+                    if dist[to].is_none() {
+                        dist[to] = Some(dist[v].unwrap() + 1);
+                        wdist[to] = wdist[v] + weight[&(v, to)];
+                        stack.push(to);
+                    }
+                }
+            }
+
+            assert_eq!(
+                dist_sum[s],
+                (dist.iter().map(|d| d.unwrap()).sum(), n as u64)
+            );
+            assert_eq!(ecc[s], *wdist.iter().max().unwrap());
+        }
+    }
+
+    // Deep path without the big stack
+    let n = 200_000;
+    let g = (0..n)
+        .map(|i| {
+            let mut es = Vec::new();
+            if i > 0 {
+                es.push(i - 1);
+            }
+            if i + 1 < n {
+                es.push(i + 1);
+            }
+            es
+        })
+        .collect::<Vec<_>>();
+    let res = DistanceSum.solve(&g);
+    let n = n as u64;
+    assert_eq!(res[0].0, n * (n - 1) / 2);
+}
@@ -1,3 +1,4 @@
+use crate::graph::HeavyLightDecomposition;
 use cargo_snippet::snippet;
 
 #[snippet("SEG_LAZY")]
@@ -128,6 +129,62 @@ pub trait SEGimpl {
     fn to_result(elem: Self::Elem) -> Self::R;
 }
 
+#[snippet("HeavyLightDecomposition-SEG_LAZY")]
+#[snippet(include = "HeavyLightDecomposition")]
+#[snippet(include = "SEG_LAZY")]
+impl HeavyLightDecomposition {
+    #[allow(dead_code)]
+    /// Apply `x` to every vertex on the path from `u` to `v`. `seg[tin[v]]` holds the value of v.
+    pub fn path_add<T: SEGimpl>(&self, seg: &mut SEG<T>, u: usize, v: usize, x: &T::A, edge: bool) {
+        for (l, r, _) in self.path_ranges(u, v, edge) {
+            seg.range_add(x, l, r);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn subtree_add<T: SEGimpl>(&self, seg: &mut SEG<T>, v: usize, x: &T::A, edge: bool) {
+        let (l, r) = self.subtree_range(v, edge);
+        seg.range_add(x, l, r);
+    }
+
+    #[allow(dead_code)]
+    /// Fold over the path from `u` to `v`. `reduce` must be commutative.
+    pub fn path_query_lazy<T: SEGimpl>(
+        &self,
+        seg: &mut SEG<T>,
+        u: usize,
+        v: usize,
+        edge: bool,
+    ) -> Option<T::R> {
+        let n = seg.n;
+        let mut res: Option<T::Elem> = None;
+        for (l, r, _) in self.path_ranges(u, v, edge) {
+            if let Some(x) = seg.q(l, r, 0, 0, n) {
+                res = Some(match res {
+                    Some(acc) => {
+                        let mut y = seg.zero.clone();
+                        T::reduce(&mut y, &acc, &x);
+                        y
+                    }
+                    None => x,
+                });
+            }
+        }
+        res.map(T::to_result)
+    }
+
+    #[allow(dead_code)]
+    pub fn subtree_query_lazy<T: SEGimpl>(
+        &self,
+        seg: &mut SEG<T>,
+        v: usize,
+        edge: bool,
+    ) -> Option<T::R> {
+        let (l, r) = self.subtree_range(v, edge);
+        seg.query(l, r)
+    }
+}
+
 #[allow(dead_code)]
 #[snippet("RangeAddSum")]
 struct RangeAddSum();
@@ -297,3 +354,61 @@ fn bench_lazy_segtree_query(b: &mut Bencher) {
         }
     });
 }
+
+#[test]
+fn test_hld_path_and_subtree() {
+    use crate::graph::{Tree, LCA};
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    let n = 200;
+    let mut g = vec![Vec::new(); n];
+    for i in 1..n {
+        let p = rng.next_u32() as usize % i;
+        g[p].push(i);
+        g[i].push(p);
+    }
+    let tree = Tree::from_neighbor_list(n, 0, &g);
+    let hld = HeavyLightDecomposition::new(&tree);
+    let lca = LCA::new(&g);
+    let mut seg: SEG<RangeAddSum> = SEG::new(n, (0, 0));
+    let mut v = vec![0; n];
+
+    for _ in 0..1000 {
+        let a = rng.next_u32() as usize % n;
+        let b = rng.next_u32() as usize % n;
+        let x = rng.next_u64() % 100;
+        let edge = rng.next_u32() % 2 == 0;
+        let l = lca.lca(a, b);
+        let path = (0..lca.dist(a, b) + 1)
+            .map(|k| lca.jump(a, b, k).unwrap())
+            .filter(|&u| !edge || u != l)
+            .collect::<Vec<_>>();
+        let subtree = (0..n)
+            .filter(|&u| lca.lca(u, a) == a && (!edge || u != a))
+            .collect::<Vec<_>>();
+
+        match rng.next_u32() % 4 {
+            0 => {
+                hld.path_add(&mut seg, a, b, &x, edge);
+                for &u in &path {
+                    v[u] += x;
+                }
+            }
+            1 => {
+                hld.subtree_add(&mut seg, a, &x, edge);
+                for &u in &subtree {
+                    v[u] += x;
+                }
+            }
+            2 => {
+                let sum = path.iter().map(|&u| v[u]).sum::<u64>();
+                assert_eq!(hld.path_query_lazy(&mut seg, a, b, edge).unwrap_or(0), sum);
+            }
+            _ => {
+                let sum = subtree.iter().map(|&u| v[u]).sum::<u64>();
+                assert_eq!(hld.subtree_query_lazy(&mut seg, a, edge).unwrap_or(0), sum);
+            }
+        }
+    }
+}
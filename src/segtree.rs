@@ -1,3 +1,4 @@
+use crate::graph::HeavyLightDecomposition;
 use crate::monoid::Monoid;
 use cargo_snippet::snippet;
 
@@ -91,6 +92,26 @@ impl<M: Monoid> SEG<M> {
     }
 }
 
+#[snippet("HeavyLightDecomposition-SEG")]
+#[snippet(include = "HeavyLightDecomposition")]
+#[snippet(include = "SEG")]
+impl HeavyLightDecomposition {
+    #[allow(dead_code)]
+    /// Fold over the path from `u` to `v`. `seg[tin[v]]` holds the value of v.
+    /// The monoid must be commutative. Use `path_ranges` otherwise.
+    pub fn path_query<M: Monoid>(&self, seg: &SEG<M>, u: usize, v: usize, edge: bool) -> M::T {
+        self.path_ranges(u, v, edge)
+            .into_iter()
+            .fold(M::id(), |acc, (l, r, _)| M::op(&acc, &seg.query(l, r)))
+    }
+
+    #[allow(dead_code)]
+    pub fn subtree_query<M: Monoid>(&self, seg: &SEG<M>, v: usize, edge: bool) -> M::T {
+        let (l, r) = self.subtree_range(v, edge);
+        seg.query(l, r)
+    }
+}
+
 #[test]
 fn test_segtree_vs_cumulative_sum() {
     use crate::monoid::SUM;
@@ -228,3 +249,40 @@ fn bench_segtree_query(b: &mut Bencher) {
         }
     });
 }
+
+#[test]
+fn test_hld_path_query() {
+    use crate::graph::{Tree, LCA};
+    use crate::monoid::SUM;
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    let n = 200;
+    let mut g = vec![Vec::new(); n];
+    for i in 1..n {
+        let p = rng.next_u32() as usize % i;
+        g[p].push(i);
+        g[i].push(p);
+    }
+    let hld = HeavyLightDecomposition::new(&Tree::from_neighbor_list(n, 0, &g));
+    let lca = LCA::new(&g);
+    let mut seg: SEG<SUM> = SEG::new(n);
+    let v = (0..n).map(|_| rng.next_u64() % 100).collect::<Vec<_>>();
+    for (i, &x) in v.iter().enumerate() {
+        seg.update(hld.tin[i], x);
+    }
+
+    for _ in 0..1000 {
+        let a = rng.next_u32() as usize % n;
+        let b = rng.next_u32() as usize % n;
+        let sum = (0..lca.dist(a, b) + 1)
+            .map(|k| v[lca.jump(a, b, k).unwrap()])
+            .sum::<u64>();
+        assert_eq!(hld.path_query(&seg, a, b, false), sum);
+        let sub = (0..n)
+            .filter(|&u| lca.lca(u, a) == a)
+            .map(|u| v[u])
+            .sum::<u64>();
+        assert_eq!(hld.subtree_query(&seg, a, false), sub);
+    }
+}
@@ -0,0 +1,752 @@
+use crate::graph::Graph;
+use crate::total::Total;
+use cargo_snippet::snippet;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+#[snippet("Cost")]
+#[snippet(include = "Total")]
+/// Edge cost for shortest path algorithms
+pub trait Cost: Copy + Ord {
+    fn zero() -> Self;
+    fn add(self, rhs: Self) -> Self;
+}
+
+#[snippet("Cost")]
+impl Cost for u64 {
+    fn zero() -> u64 {
+        0
+    }
+    fn add(self, rhs: u64) -> u64 {
+        self + rhs
+    }
+}
+
+#[snippet("Cost")]
+impl Cost for i64 {
+    fn zero() -> i64 {
+        0
+    }
+    fn add(self, rhs: i64) -> i64 {
+        self + rhs
+    }
+}
+
+#[snippet("Cost")]
+impl Cost for usize {
+    fn zero() -> usize {
+        0
+    }
+    fn add(self, rhs: usize) -> usize {
+        self + rhs
+    }
+}
+
+#[snippet("Cost")]
+impl Cost for Total<f64> {
+    fn zero() -> Total<f64> {
+        Total(0.0)
+    }
+    fn add(self, rhs: Total<f64>) -> Total<f64> {
+        Total(self.0 + rhs.0)
+    }
+}
+
+#[snippet("ShortestPath")]
+#[snippet(include = "Cost")]
+#[snippet(include = "Graph")]
+#[derive(Clone, Debug)]
+/// Result of single/multi source shortest path algorithms
+pub struct ShortestPath<C> {
+    /// None if unreachable
+    pub dist: Vec<Option<C>>,
+    /// Previous vertex on a shortest path. None for sources and unreachable vertices
+    pub prev: Vec<Option<usize>>,
+    /// Reachable vertices in the order their distances were fixed
+    pub order: Vec<usize>,
+}
+
+#[snippet("ShortestPath")]
+impl<C: Cost> ShortestPath<C> {
+    #[allow(dead_code)]
+    fn init(n: usize, sources: &[usize]) -> ShortestPath<C> {
+        let mut dist = vec![None; n];
+        for &s in sources {
+            dist[s] = Some(C::zero());
+        }
+        ShortestPath {
+            dist,
+            prev: vec![None; n],
+            order: Vec::with_capacity(n),
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Vertices of a shortest path from one of the sources to `v`
+    pub fn path_to(&self, v: usize) -> Option<Vec<usize>> {
+        self.dist[v]?;
+        let mut path = vec![v];
+        let mut v = v;
+        while let Some(p) = self.prev[v] {
+            path.push(p);
+            v = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    #[allow(dead_code)]
+    /// Number of shortest paths to each vertex modulo `p`.
+    /// `cost` converts an edge weight into its cost. Costs must be positive.
+    pub fn count_paths<W, F: Fn(&W) -> C>(&self, g: &Graph<W>, cost: F, p: u64) -> Vec<u64> {
+        let mut count = vec![0; self.dist.len()];
+        for &v in &self.order {
+            if self.prev[v].is_none() {
+                count[v] = 1 % p;
+            }
+            let d = self.dist[v].unwrap();
+            for &(to, id) in g.adj(v) {
+                if self.dist[to] == Some(d.add(cost(g.weight(id)))) {
+                    count[to] = (count[to] + count[v]) % p;
+                }
+            }
+        }
+        count
+    }
+}
+
+#[snippet("dijkstra")]
+#[snippet(include = "ShortestPath")]
+#[allow(dead_code)]
+/// Dijkstra's algorithm with a binary heap. O((V + E) log V)
+pub fn dijkstra<C: Cost>(g: &Graph<C>, sources: &[usize]) -> ShortestPath<C> {
+    let mut sp = ShortestPath::init(g.len(), sources);
+    let mut done = vec![false; g.len()];
+    let mut heap = BinaryHeap::new();
+    for &s in sources {
+        heap.push(Reverse((C::zero(), s)));
+    }
+
+    while let Some(Reverse((d, v))) = heap.pop() {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        sp.order.push(v);
+
+        for &(to, id) in g.adj(v) {
+            let nd = d.add(*g.weight(id));
+            if sp.dist[to].map(|x| nd < x).unwrap_or(true) {
+                sp.dist[to] = Some(nd);
+                sp.prev[to] = Some(v);
+                heap.push(Reverse((nd, to)));
+            }
+        }
+    }
+
+    sp
+}
+
+#[snippet("dijkstra_dense")]
+#[snippet(include = "ShortestPath")]
+#[allow(dead_code)]
+/// Dijkstra's algorithm on an adjacency matrix. O(V^2)
+/// `mat[u][v]` is the cost of the edge u -> v
+pub fn dijkstra_dense<C: Cost>(mat: &[Vec<Option<C>>], sources: &[usize]) -> ShortestPath<C> {
+    let n = mat.len();
+    let mut sp = ShortestPath::init(n, sources);
+    let mut used = vec![false; n];
+
+    loop {
+        let mut next: Option<(C, usize)> = None;
+        for (v, &u) in used.iter().enumerate() {
+            if let (false, Some(d)) = (u, sp.dist[v]) {
+                if next.map(|(x, _)| d < x).unwrap_or(true) {
+                    next = Some((d, v));
+                }
+            }
+        }
+        let (d, v) = match next {
+            Some(x) => x,
+            None => break,
+        };
+        used[v] = true;
+        sp.order.push(v);
+
+        for (to, c) in mat[v].iter().enumerate() {
+            if let Some(c) = *c {
+                let nd = d.add(c);
+                if !used[to] && sp.dist[to].map(|x| nd < x).unwrap_or(true) {
+                    sp.dist[to] = Some(nd);
+                    sp.prev[to] = Some(v);
+                }
+            }
+        }
+    }
+
+    sp
+}
+
+#[snippet("bfs01")]
+#[snippet(include = "ShortestPath")]
+#[allow(dead_code)]
+/// 0-1 BFS. Every edge cost must be zero or one fixed positive value. O(V + E)
+pub fn bfs01<C: Cost>(g: &Graph<C>, sources: &[usize]) -> ShortestPath<C> {
+    let mut sp: ShortestPath<C> = ShortestPath::init(g.len(), sources);
+    let mut done = vec![false; g.len()];
+    let mut que = sources.iter().cloned().collect::<VecDeque<_>>();
+
+    while let Some(v) = que.pop_front() {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        sp.order.push(v);
+        let d = sp.dist[v].unwrap();
+
+        for &(to, id) in g.adj(v) {
+            let w = *g.weight(id);
+            let nd = d.add(w);
+            if sp.dist[to].map(|x| nd < x).unwrap_or(true) {
+                sp.dist[to] = Some(nd);
+                sp.prev[to] = Some(v);
+                if w == C::zero() {
+                    que.push_front(to);
+                } else {
+                    que.push_back(to);
+                }
+            }
+        }
+    }
+
+    sp
+}
+
+#[snippet("bfs")]
+#[snippet(include = "ShortestPath")]
+#[allow(dead_code)]
+/// BFS ignoring edge weights. O(V + E)
+pub fn bfs<W>(g: &Graph<W>, sources: &[usize]) -> ShortestPath<usize> {
+    let mut sp = ShortestPath::init(g.len(), sources);
+    let mut que = VecDeque::new();
+    let mut srcs = sources.to_vec();
+    srcs.sort();
+    srcs.dedup();
+    for s in srcs {
+        sp.order.push(s);
+        que.push_back(s);
+    }
+
+    while let Some(v) = que.pop_front() {
+        let d = sp.dist[v].unwrap() + 1;
+        for &(to, _) in g.adj(v) {
+            if sp.dist[to].is_none() {
+                sp.dist[to] = Some(d);
+                sp.prev[to] = Some(v);
+                sp.order.push(to);
+                que.push_back(to);
+            }
+        }
+    }
+
+    sp
+}
+
+#[snippet("bellman_ford")]
+#[snippet(include = "ShortestPath")]
+#[allow(dead_code)]
+/// Bellman-Ford algorithm. O(VE)
+/// Returns shortest paths and whether each vertex has distance -infinity,
+/// i.e. it is reachable from a negative cycle reachable from the sources.
+/// `dist` and `prev` of such vertices are None.
+pub fn bellman_ford<C: Cost>(g: &Graph<C>, sources: &[usize]) -> (ShortestPath<C>, Vec<bool>) {
+    let n = g.len();
+    let mut sp: ShortestPath<C> = ShortestPath::init(n, sources);
+    let mut neg_inf = vec![false; n];
+
+    for i in 0..2 * n {
+        let mut updated = false;
+        for v in 0..n {
+            let d = match sp.dist[v] {
+                Some(d) => d,
+                None => continue,
+            };
+            for &(to, id) in g.adj(v) {
+                let nd = d.add(*g.weight(id));
+                if sp.dist[to].map(|x| nd < x).unwrap_or(true) {
+                    sp.dist[to] = Some(nd);
+                    sp.prev[to] = Some(v);
+                    updated = true;
+                    if i >= n - 1 {
+                        neg_inf[to] = true;
+                    }
+                }
+                if neg_inf[v] && !neg_inf[to] {
+                    neg_inf[to] = true;
+                    updated = true;
+                }
+            }
+        }
+        if !updated {
+            break;
+        }
+    }
+
+    for v in (0..n).filter(|&v| neg_inf[v]) {
+        sp.dist[v] = None;
+        sp.prev[v] = None;
+    }
+    let mut order = (0..n).filter(|&v| sp.dist[v].is_some()).collect::<Vec<_>>();
+    order.sort_by_key(|&v| sp.dist[v]);
+    sp.order = order;
+    (sp, neg_inf)
+}
+
+#[snippet("negative_cycle")]
+#[snippet(include = "Cost")]
+#[snippet(include = "Graph")]
+#[allow(dead_code)]
+/// Find a negative cycle anywhere in the graph by Bellman-Ford. O(VE)
+/// Returns (vertices, edge ids) along the cycle.
+pub fn negative_cycle<C: Cost>(g: &Graph<C>) -> Option<(Vec<usize>, Vec<usize>)> {
+    let n = g.len();
+    let mut dist = vec![C::zero(); n];
+    let mut prev = vec![None; n];
+    let mut last = None;
+
+    for _ in 0..n {
+        last = None;
+        for v in 0..n {
+            for &(to, id) in g.adj(v) {
+                let nd = dist[v].add(*g.weight(id));
+                if nd < dist[to] {
+                    dist[to] = nd;
+                    prev[to] = Some((v, id));
+                    last = Some(to);
+                }
+            }
+        }
+        last?;
+    }
+
+    let mut v = last?;
+    for _ in 0..n {
+        v = prev[v].unwrap().0;
+    }
+
+    let mut vs = Vec::new();
+    let mut es = Vec::new();
+    let mut u = v;
+    loop {
+        let (p, id) = prev[u].unwrap();
+        vs.push(p);
+        es.push(id);
+        u = p;
+        if u == v {
+            break;
+        }
+    }
+    vs.reverse();
+    es.reverse();
+    Some((vs, es))
+}
+
+#[snippet("spfa")]
+#[snippet(include = "ShortestPath")]
+#[allow(dead_code)]
+/// Shortest Path Faster Algorithm. Worst case O(VE)
+/// Returns None if a negative cycle is reachable from the sources.
+pub fn spfa<C: Cost>(g: &Graph<C>, sources: &[usize]) -> Option<ShortestPath<C>> {
+    let n = g.len();
+    let mut sp: ShortestPath<C> = ShortestPath::init(n, sources);
+    let mut len = vec![0; n];
+    let mut in_que = vec![false; n];
+    let mut que = VecDeque::new();
+    for &s in sources {
+        if !in_que[s] {
+            in_que[s] = true;
+            que.push_back(s);
+        }
+    }
+
+    while let Some(v) = que.pop_front() {
+        in_que[v] = false;
+        let d = sp.dist[v].unwrap();
+        for &(to, id) in g.adj(v) {
+            let nd = d.add(*g.weight(id));
+            if sp.dist[to].map(|x| nd < x).unwrap_or(true) {
+                sp.dist[to] = Some(nd);
+                sp.prev[to] = Some(v);
+                len[to] = len[v] + 1;
+                if len[to] >= n {
+                    return None;
+                }
+                if !in_que[to] {
+                    in_que[to] = true;
+                    que.push_back(to);
+                }
+            }
+        }
+    }
+
+    let mut order = (0..n).filter(|&v| sp.dist[v].is_some()).collect::<Vec<_>>();
+    order.sort_by_key(|&v| sp.dist[v]);
+    sp.order = order;
+    Some(sp)
+}
+
+#[snippet("FloydWarshall")]
+#[snippet(include = "Cost")]
+#[snippet(include = "Graph")]
+/// All pairs shortest paths. O(V^3)
+pub struct FloydWarshall<C> {
+    /// None if unreachable. Meaningless where `neg_inf` is true
+    pub dist: Vec<Vec<Option<C>>>,
+    /// true if there is an arbitrarily short walk through a negative cycle
+    pub neg_inf: Vec<Vec<bool>>,
+    next: Vec<Vec<usize>>,
+}
+
+#[snippet("FloydWarshall")]
+impl<C: Cost> FloydWarshall<C> {
+    #[allow(dead_code)]
+    pub fn new(g: &Graph<C>) -> FloydWarshall<C> {
+        let n = g.len();
+        let mut dist = vec![vec![None; n]; n];
+        let mut next = vec![vec![!0; n]; n];
+        let mut reach = vec![vec![false; n]; n];
+        for v in 0..n {
+            dist[v][v] = Some(C::zero());
+            next[v][v] = v;
+            reach[v][v] = true;
+            for &(to, id) in g.adj(v) {
+                let w = *g.weight(id);
+                reach[v][to] = true;
+                if dist[v][to].map(|x| w < x).unwrap_or(true) {
+                    dist[v][to] = Some(w);
+                    next[v][to] = to;
+                }
+            }
+        }
+
+        // A vertex on a negative cycle is not used as an intermediate vertex
+        // so that distances don't diverge.
+        let mut on_cycle = vec![false; n];
+        for k in 0..n {
+            let rk = reach[k].clone();
+            for ri in reach.iter_mut().filter(|ri| ri[k]) {
+                for (x, &y) in ri.iter_mut().zip(&rk) {
+                    *x |= y;
+                }
+            }
+
+            if dist[k][k].map(|x| x < C::zero()).unwrap_or(false) {
+                on_cycle[k] = true;
+                continue;
+            }
+            for i in 0..n {
+                let dik = match dist[i][k] {
+                    Some(d) => d,
+                    None => continue,
+                };
+                for j in 0..n {
+                    if let Some(dkj) = dist[k][j] {
+                        let d = dik.add(dkj);
+                        if dist[i][j].map(|x| d < x).unwrap_or(true) {
+                            dist[i][j] = Some(d);
+                            next[i][j] = next[i][k];
+                        }
+                    }
+                }
+            }
+        }
+        for k in 0..n {
+            if dist[k][k].map(|x| x < C::zero()).unwrap_or(false) {
+                on_cycle[k] = true;
+            }
+        }
+
+        let mut neg_inf = vec![vec![false; n]; n];
+        for k in (0..n).filter(|&k| on_cycle[k]) {
+            for i in (0..n).filter(|&i| reach[i][k]) {
+                for (x, &y) in neg_inf[i].iter_mut().zip(&reach[k]) {
+                    *x |= y;
+                }
+            }
+        }
+
+        FloydWarshall {
+            dist,
+            neg_inf,
+            next,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn has_negative_cycle(&self) -> bool {
+        (0..self.dist.len()).any(|v| self.neg_inf[v][v])
+    }
+
+    #[allow(dead_code)]
+    /// Vertices of a shortest path from `u` to `v`
+    pub fn path(&self, u: usize, v: usize) -> Option<Vec<usize>> {
+        if self.dist[u][v].is_none() || self.neg_inf[u][v] {
+            return None;
+        }
+        let mut path = vec![u];
+        let mut u = u;
+        while u != v {
+            u = self.next[u][v];
+            path.push(u);
+        }
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+fn random_graph<R: rand::Rng>(rng: &mut R, n: usize, m: usize, max_cost: u64) -> Graph<u64> {
+    let edges = (0..m)
+        .map(|_| {
+            (
+                rng.next_u32() as usize % n,
+                rng.next_u32() as usize % n,
+                rng.next_u64() % max_cost + 1,
+            )
+        })
+        .collect::<Vec<_>>();
+    Graph::directed(n, &edges)
+}
+
+#[cfg(test)]
+fn floyd_naive(g: &Graph<u64>) -> Vec<Vec<Option<u64>>> {
+    let n = g.len();
+    let mut d = vec![vec![None; n]; n];
+    for (v, row) in d.iter_mut().enumerate() {
+        row[v] = Some(0);
+    }
+    for &(u, v, w) in &g.edges {
+        if d[u][v].map(|x| w < x).unwrap_or(true) {
+            d[u][v] = Some(w);
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                if let (Some(a), Some(b)) = (d[i][k], d[k][j]) {
+                    if d[i][j].map(|x| a + b < x).unwrap_or(true) {
+                        d[i][j] = Some(a + b);
+                    }
+                }
+            }
+        }
+    }
+    d
+}
+
+#[test]
+fn test_shortest_path_vs_floyd() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..20 {
+        let n = 30;
+        let g = random_graph(&mut rng, n, 120, 10);
+        let g01 = Graph::directed(
+            n,
+            &g.edges
+                .iter()
+                .map(|&(u, v, w)| (u, v, w % 2))
+                .collect::<Vec<_>>(),
+        );
+        let mut mat = vec![vec![None; n]; n];
+        for &(u, v, w) in &g.edges {
+            if mat[u][v].map(|x| w < x).unwrap_or(true) {
+                mat[u][v] = Some(w);
+            }
+        }
+        let d = floyd_naive(&g);
+        let d01 = floyd_naive(&g01);
+        let d1 = floyd_naive(&Graph::directed(
+            n,
+            &g.edges
+                .iter()
+                .map(|&(u, v, _)| (u, v, 1))
+                .collect::<Vec<_>>(),
+        ));
+
+        for s in 0..n {
+            let sp = dijkstra(&g, &[s]);
+            let dense = dijkstra_dense(&mat, &[s]);
+            let sp01 = bfs01(&g01, &[s]);
+            let sp1 = bfs(&g, &[s]);
+            assert_eq!(sp.dist, d[s]);
+            assert_eq!(dense.dist, d[s]);
+            assert_eq!(sp01.dist, d01[s]);
+            assert_eq!(
+                sp1.dist,
+                d1[s]
+                    .iter()
+                    .map(|x| x.map(|x| x as usize))
+                    .collect::<Vec<_>>()
+            );
+
+            for (t, &dt) in d[s].iter().enumerate() {
+                if let Some(path) = sp.path_to(t) {
+                    assert_eq!(path[0], s);
+                    assert_eq!(*path.last().unwrap(), t);
+                    let len = path
+                        .windows(2)
+                        .map(|w| mat[w[0]][w[1]].unwrap())
+                        .sum::<u64>();
+                    assert_eq!(Some(len), dt);
+                } else {
+                    assert!(dt.is_none());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_shortest_path_multi_source_and_count() {
+    // 0 -> {1, 2} -> 3 -> 4, 5 -> 4
+    let g = Graph::directed(
+        6,
+        &[
+            (0, 1, 1u64),
+            (0, 2, 1),
+            (1, 3, 1),
+            (2, 3, 1),
+            (3, 4, 1),
+            (5, 4, 2),
+        ],
+    );
+    let sp = dijkstra(&g, &[0]);
+    assert_eq!(
+        sp.count_paths(&g, |&w| w, 1_000_000_007),
+        vec![1, 1, 1, 2, 2, 0]
+    );
+    assert_eq!(sp.path_to(5), None);
+
+    let sp = dijkstra(&g, &[0, 5]);
+    assert_eq!(sp.dist[4], Some(2));
+    assert_eq!(sp.path_to(4), Some(vec![5, 4]));
+
+    let sp = bfs(&g, &[0, 5]);
+    assert_eq!(sp.dist[4], Some(1));
+    assert_eq!(sp.count_paths(&g, |_| 1, 1_000_000_007)[3], 2);
+
+    let gf = Graph::undirected(
+        3,
+        &[(0, 1, Total(0.5)), (1, 2, Total(0.25)), (0, 2, Total(1.0))],
+    );
+    let sp = dijkstra(&gf, &[0]);
+    assert!(sp.dist[2] == Some(Total(0.75)));
+    assert_eq!(sp.path_to(2), Some(vec![0, 1, 2]));
+}
+
+#[test]
+fn test_negative_edges() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[5, 4, 3, 2, 1]);
+    let n = 25;
+
+    for iter in 0..40 {
+        // With potentials there is no negative cycle, and Dijkstra on the
+        // reweighted graph gives the answer.
+        let g = random_graph(&mut rng, n, 80, 20);
+        let pot = (0..n)
+            .map(|_| (rng.next_u32() % 50) as i64)
+            .collect::<Vec<_>>();
+        let mut edges = g
+            .edges
+            .iter()
+            .map(|&(u, v, w)| (u, v, w as i64 + pot[u] - pot[v]))
+            .collect::<Vec<_>>();
+        if iter % 2 == 1 {
+            // Add a negative cycle
+            let a = rng.next_u32() as usize % n;
+            let b = rng.next_u32() as usize % n;
+            edges.push((a, b, -100));
+            edges.push((b, a, -100));
+        }
+        let gi = Graph::directed(n, &edges);
+        let fw = FloydWarshall::new(&gi);
+        let cycle = negative_cycle(&gi);
+        assert_eq!(fw.has_negative_cycle(), iter % 2 == 1);
+        assert_eq!(cycle.is_some(), iter % 2 == 1);
+
+        if let Some((vs, es)) = cycle {
+            assert_eq!(vs.len(), es.len());
+            let mut sum = 0;
+            for (i, &e) in es.iter().enumerate() {
+                let (u, v, w) = gi.edges[e];
+                assert_eq!(u, vs[i]);
+                assert_eq!(v, vs[(i + 1) % vs.len()]);
+                sum += w;
+            }
+            assert!(sum < 0);
+        }
+
+        let d = floyd_naive(&g);
+        for s in 0..n {
+            let (bf, neg_inf) = bellman_ford(&gi, &[s]);
+            let sp = spfa(&gi, &[s]);
+            assert_eq!(sp.is_some(), neg_inf.iter().all(|&b| !b));
+            assert_eq!(neg_inf, fw.neg_inf[s]);
+
+            for t in 0..n {
+                if neg_inf[t] {
+                    assert!(fw.path(s, t).is_none());
+                    assert_eq!(bf.dist[t], None);
+                    assert_eq!(bf.path_to(t), None);
+                    continue;
+                }
+                assert_eq!(bf.dist[t], fw.dist[s][t]);
+                if iter % 2 == 0 {
+                    let expected = d[s][t].map(|x| x as i64 + pot[s] - pot[t]);
+                    assert_eq!(bf.dist[t], expected);
+                    assert_eq!(sp.as_ref().unwrap().dist[t], expected);
+                }
+                if let Some(path) = fw.path(s, t) {
+                    let len = path
+                        .windows(2)
+                        .map(|w| {
+                            gi.edges
+                                .iter()
+                                .filter(|e| e.0 == w[0] && e.1 == w[1])
+                                .map(|e| e.2)
+                                .min()
+                                .unwrap()
+                        })
+                        .sum::<i64>();
+                    assert_eq!(Some(len), fw.dist[s][t]);
+                    assert!(bf.path_to(t).is_some());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_bellman_ford_path_through_negative_cycle() {
+    let g = Graph::directed(
+        6,
+        &[
+            (0, 1, 1i64),
+            (1, 2, -3),
+            (2, 1, 1),
+            (2, 3, 1),
+            (3, 4, 1),
+            (0, 5, 2),
+        ],
+    );
+    let (sp, neg_inf) = bellman_ford(&g, &[0]);
+    assert_eq!(neg_inf, vec![false, true, true, true, true, false]);
+    for v in 1..5 {
+        assert_eq!(sp.dist[v], None);
+        assert_eq!(sp.path_to(v), None);
+    }
+    assert_eq!(sp.path_to(5), Some(vec![0, 5]));
+    assert_eq!(sp.order, vec![0, 5]);
+}
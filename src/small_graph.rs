@@ -0,0 +1,460 @@
+use crate::bitset::BitSet;
+use crate::shortest_path::Cost;
+use cargo_snippet::snippet;
+
+#[snippet("VertexSet")]
+#[snippet(include = "BitSet")]
+/// Set of vertices used as an adjacency row.
+/// Implemented for `u64` (n <= 64), `u128` (n <= 128) and `BitSet`.
+pub trait VertexSet: Clone {
+    fn empty(n: usize) -> Self;
+    fn insert(&mut self, i: usize);
+    fn remove(&mut self, i: usize);
+    fn contains(&self, i: usize) -> bool;
+    /// Smallest element
+    fn first(&self) -> Option<usize>;
+    fn count(&self) -> usize;
+    fn intersection(&self, other: &Self) -> Self;
+    fn difference(&self, other: &Self) -> Self;
+}
+
+#[snippet("VertexSet")]
+macro_rules! impl_vertex_set {
+    ($t:ty) => {
+        impl VertexSet for $t {
+            fn empty(_n: usize) -> Self {
+                0
+            }
+            fn insert(&mut self, i: usize) {
+                *self |= 1 << i;
+            }
+            fn remove(&mut self, i: usize) {
+                *self &= !(1 << i);
+            }
+            fn contains(&self, i: usize) -> bool {
+                *self >> i & 1 == 1
+            }
+            fn first(&self) -> Option<usize> {
+                if *self == 0 {
+                    None
+                } else {
+                    Some(self.trailing_zeros() as usize)
+                }
+            }
+            fn count(&self) -> usize {
+                self.count_ones() as usize
+            }
+            fn intersection(&self, other: &Self) -> Self {
+                *self & *other
+            }
+            fn difference(&self, other: &Self) -> Self {
+                *self & !*other
+            }
+        }
+    };
+}
+
+#[snippet("VertexSet")]
+impl_vertex_set!(u64);
+#[snippet("VertexSet")]
+impl_vertex_set!(u128);
+
+#[snippet("VertexSet")]
+impl VertexSet for BitSet {
+    fn empty(n: usize) -> Self {
+        BitSet::new(n)
+    }
+    fn insert(&mut self, i: usize) {
+        self.set(i, true);
+    }
+    fn remove(&mut self, i: usize) {
+        self.set(i, false);
+    }
+    fn contains(&self, i: usize) -> bool {
+        self[i]
+    }
+    fn first(&self) -> Option<usize> {
+        self.first_one()
+    }
+    fn count(&self) -> usize {
+        self.count_ones() as usize
+    }
+    fn intersection(&self, other: &Self) -> Self {
+        self.clone() & other
+    }
+    fn difference(&self, other: &Self) -> Self {
+        let common = self.clone() & other;
+        self.clone() ^ &common
+    }
+}
+
+#[snippet("max_clique")]
+#[snippet(include = "VertexSet")]
+#[allow(dead_code)]
+/// Maximum clique by branch and bound with greedy coloring bounds (Tomita's MCQ).
+/// `adj[v]` must not contain v. Practical for n <= 60 on dense graphs and more on sparse ones.
+/// Returns the vertices of a maximum clique in ascending order.
+pub fn max_clique<S: VertexSet>(adj: &[S]) -> Vec<usize> {
+    fn expand<S: VertexSet>(adj: &[S], clique: &mut Vec<usize>, mut p: S, best: &mut Vec<usize>) {
+        // Color p greedily. A clique uses at most one vertex of each color.
+        let mut order = Vec::with_capacity(p.count());
+        let mut rest = p.clone();
+        let mut color = 0;
+        while rest.first().is_some() {
+            color += 1;
+            let mut avail = rest.clone();
+            while let Some(v) = avail.first() {
+                avail.remove(v);
+                avail = avail.difference(&adj[v]);
+                rest.remove(v);
+                order.push((v, color));
+            }
+        }
+
+        for &(v, c) in order.iter().rev() {
+            if clique.len() + c <= best.len() {
+                return;
+            }
+            clique.push(v);
+            let q = p.intersection(&adj[v]);
+            if q.first().is_some() {
+                expand(adj, clique, q, best);
+            } else if clique.len() > best.len() {
+                *best = clique.clone();
+            }
+            clique.pop();
+            p.remove(v);
+        }
+    }
+
+    let n = adj.len();
+    let mut all = S::empty(n);
+    for v in 0..n {
+        all.insert(v);
+    }
+    let mut best = Vec::new();
+    if n > 0 {
+        expand(adj, &mut Vec::new(), all, &mut best);
+    }
+    best.sort();
+    best
+}
+
+#[snippet("max_independent_set")]
+#[snippet(include = "max_clique")]
+#[allow(dead_code)]
+/// Maximum independent set as a maximum clique of the complement graph
+pub fn max_independent_set<S: VertexSet>(adj: &[S]) -> Vec<usize> {
+    let n = adj.len();
+    let complement = (0..n)
+        .map(|u| {
+            let mut row = S::empty(n);
+            for v in 0..n {
+                if u != v && !adj[u].contains(v) {
+                    row.insert(v);
+                }
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+    max_clique(&complement)
+}
+
+#[snippet("chromatic_number")]
+#[allow(dead_code)]
+/// Chromatic number by inclusion-exclusion over independent sets. O(2^n n)
+/// Counting is done modulo a fixed prime, so the answer would be too large
+/// if a true nonzero count were a multiple of it. This depends only on the input.
+pub fn chromatic_number(adj: &[u64]) -> usize {
+    const P: u64 = 1_000_000_007;
+    let n = adj.len();
+    if n == 0 {
+        return 0;
+    }
+
+    // ind[s]: number of independent sets contained in s
+    let mut ind = vec![0u64; 1 << n];
+    ind[0] = 1;
+    for s in 1..1usize << n {
+        let v = s.trailing_zeros() as usize;
+        let t = s & !(1 << v);
+        ind[s] = (ind[t] + ind[t & !adj[v] as usize]) % P;
+    }
+
+    let sign = (0..1usize << n)
+        .map(|s| (n - s.count_ones() as usize) % 2 == 1)
+        .collect::<Vec<_>>();
+    let mut pow = vec![1u64; 1 << n];
+    for k in 1..n {
+        let mut sum = 0;
+        for ((p, &x), &neg) in pow.iter_mut().zip(ind.iter()).zip(sign.iter()) {
+            *p = *p * x % P;
+            sum = if neg { sum + P - *p } else { sum + *p } % P;
+        }
+        // Number of ways to cover all vertices with k independent sets
+        if sum != 0 {
+            return k;
+        }
+    }
+    n
+}
+
+#[snippet("shortest_hamiltonian_path")]
+#[snippet(include = "Cost")]
+#[allow(dead_code)]
+/// Bit DP over subsets. O(2^n n^2)
+/// `dist[u][v]` is the cost of the edge u -> v.
+/// If `cycle` is true, finds a shortest Hamiltonian cycle (TSP) through vertex 0;
+/// the returned path starts at 0 and the edge back to 0 is implied.
+/// Returns (cost, vertices in order).
+pub fn shortest_hamiltonian_path<C: Cost>(
+    dist: &[Vec<Option<C>>],
+    cycle: bool,
+) -> Option<(C, Vec<usize>)> {
+    let n = dist.len();
+    if n == 0 {
+        return None;
+    }
+    if n == 1 {
+        return Some((C::zero(), vec![0]));
+    }
+
+    let full = (1 << n) - 1;
+    // dp[s][v]: (cost, previous vertex) of a path visiting s and ending at v
+    let mut dp: Vec<Vec<Option<(C, usize)>>> = vec![vec![None; n]; 1 << n];
+    for v in 0..n {
+        if !cycle || v == 0 {
+            dp[1 << v][v] = Some((C::zero(), !0));
+        }
+    }
+    for s in 1..full {
+        for v in 0..n {
+            let c = match dp[s][v] {
+                Some((c, _)) => c,
+                None => continue,
+            };
+            for (to, d) in dist[v].iter().enumerate() {
+                if s >> to & 1 == 1 {
+                    continue;
+                }
+                if let Some(d) = *d {
+                    let nc = c.add(d);
+                    let e = &mut dp[s | 1 << to][to];
+                    if e.map(|(x, _)| nc < x).unwrap_or(true) {
+                        *e = Some((nc, v));
+                    }
+                }
+            }
+        }
+    }
+
+    let (cost, mut v) = (0..n)
+        .filter_map(|v| {
+            let (c, _) = dp[full][v]?;
+            if cycle {
+                Some((c.add(dist[v][0]?), v))
+            } else {
+                Some((c, v))
+            }
+        })
+        .min()?;
+
+    let mut path = Vec::with_capacity(n);
+    let mut s = full;
+    while v != !0 {
+        path.push(v);
+        let (_, p) = dp[s][v].unwrap();
+        s &= !(1 << v);
+        v = p;
+    }
+    path.reverse();
+    Some((cost, path))
+}
+
+#[cfg(test)]
+fn random_adjacency<R: rand::Rng>(rng: &mut R, n: usize, p: u32) -> Vec<u64> {
+    let mut adj = vec![0u64; n];
+    for u in 0..n {
+        for v in 0..u {
+            if rng.next_u32() % 100 < p {
+                adj[u] |= 1 << v;
+                adj[v] |= 1 << u;
+            }
+        }
+    }
+    adj
+}
+
+#[test]
+fn test_max_clique_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..200 {
+        let n = rng.next_u32() as usize % 13;
+        let p = rng.next_u32() % 100;
+        let adj = random_adjacency(&mut rng, n, p);
+
+        let is_clique = |s: u64| (0..n).all(|v| s >> v & 1 == 0 || s & !(1 << v) & !adj[v] == 0);
+        let brute = (0..1u64 << n)
+            .filter(|&s| is_clique(s))
+            .map(|s| s.count_ones() as usize)
+            .max()
+            .unwrap();
+
+        let res = max_clique(&adj);
+        assert_eq!(res.len(), brute);
+        assert!(is_clique(res.iter().map(|&v| 1 << v).sum()));
+
+        let adj128 = adj.iter().map(|&x| x as u128).collect::<Vec<_>>();
+        assert_eq!(max_clique(&adj128), res);
+        let adj_bs = adj
+            .iter()
+            .map(|&x| {
+                let mut b = BitSet::new(n);
+                for v in 0..n {
+                    b.set(v, x >> v & 1 == 1);
+                }
+                b
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(max_clique(&adj_bs), res);
+
+        let mis = max_independent_set(&adj);
+        let is_independent = |s: u64| (0..n).all(|v| s >> v & 1 == 0 || s & adj[v] == 0);
+        assert!(is_independent(mis.iter().map(|&v| 1 << v).sum()));
+        assert_eq!(
+            mis.len(),
+            (0..1u64 << n)
+                .filter(|&s| is_independent(s))
+                .map(|s| s.count_ones() as usize)
+                .max()
+                .unwrap()
+        );
+    }
+
+    // More than 128 vertices
+    let n = 150;
+    let mut adj = vec![BitSet::new(n); n];
+    let hidden = (0..12).map(|i| i * 12 + 1).collect::<Vec<_>>();
+    for u in 0..n {
+        for v in 0..u {
+            let edge = (hidden.contains(&u) && hidden.contains(&v)) || rng.next_u32() % 10 == 0;
+            adj[u].set(v, edge);
+            adj[v].set(u, edge);
+        }
+    }
+    let res = max_clique(&adj);
+    assert_eq!(res, hidden);
+}
+
+#[test]
+fn test_chromatic_number_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    fn colorable(adj: &[u64], color: &mut Vec<usize>, k: usize) -> bool {
+        let v = color.len();
+        if v == adj.len() {
+            return true;
+        }
+        for c in 0..k {
+            if (0..v).all(|u| adj[v] >> u & 1 == 0 || color[u] != c) {
+                color.push(c);
+                if colorable(adj, color, k) {
+                    return true;
+                }
+                color.pop();
+            }
+        }
+        false
+    }
+
+    for _ in 0..200 {
+        let n = rng.next_u32() as usize % 9;
+        let p = rng.next_u32() % 100;
+        let adj = random_adjacency(&mut rng, n, p);
+        let brute = (0..=n)
+            .find(|&k| colorable(&adj, &mut Vec::new(), k))
+            .unwrap();
+        assert_eq!(chromatic_number(&adj), brute);
+    }
+
+    // Complete multipartite graphs. The counts of covers far exceed the modulus
+    let n = 18;
+    for parts in 1..=6 {
+        let adj = (0..n)
+            .map(|v| {
+                (0..n)
+                    .filter(|&u| u % parts != v % parts)
+                    .fold(0u64, |a, u| a | 1 << u)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(chromatic_number(&adj), parts);
+    }
+}
+
+#[test]
+fn test_shortest_hamiltonian_path_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![Vec::new()];
+        }
+        let mut res = Vec::new();
+        for p in permutations(n - 1) {
+            for i in 0..n {
+                let mut q = p.clone();
+                q.insert(i, n - 1);
+                res.push(q);
+            }
+        }
+        res
+    }
+
+    for _ in 0..100 {
+        let n = rng.next_u32() as usize % 6 + 1;
+        let dist = (0..n)
+            .map(|_| {
+                (0..n)
+                    .map(|_| {
+                        if rng.next_u32() % 4 == 0 {
+                            None
+                        } else {
+                            Some(rng.next_u64() % 100)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let cost = |p: &[usize], cycle: bool| {
+            let mut c = 0;
+            for w in p.windows(2) {
+                c += dist[w[0]][w[1]]?;
+            }
+            if cycle && n > 1 {
+                c += dist[p[n - 1]][p[0]]?;
+            }
+            Some(c)
+        };
+
+        for &cycle in &[false, true] {
+            let brute = permutations(n)
+                .into_iter()
+                .filter(|p| !cycle || p[0] == 0)
+                .filter_map(|p| cost(&p, cycle))
+                .min();
+            let res = shortest_hamiltonian_path(&dist, cycle);
+            assert_eq!(res.as_ref().map(|r| r.0), brute);
+            if let Some((c, path)) = res {
+                assert_eq!(path.len(), n);
+                assert_eq!(cost(&path, cycle), Some(c));
+                if cycle {
+                    assert_eq!(path[0], 0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::bitset::BitSet;
+use crate::graph::{Adjacency, Graph};
+use crate::shortest_path::Cost;
+use cargo_snippet::snippet;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[snippet("topological_sort")]
+#[snippet(include = "Adjacency")]
+#[allow(dead_code)]
+/// Kahn's algorithm.
+/// Returns a topological order, or the vertices of a cycle if the graph is not a DAG.
+pub fn topological_sort<G: Adjacency + ?Sized>(g: &G) -> Result<Vec<usize>, Vec<usize>> {
+    let n = g.vertices();
+    let mut indeg = vec![0; n];
+    for v in 0..n {
+        for i in 0..g.degree(v) {
+            indeg[g.neighbor(v, i)] += 1;
+        }
+    }
+
+    let mut order = (0..n).filter(|&v| indeg[v] == 0).collect::<Vec<_>>();
+    let mut head = 0;
+    while head < order.len() {
+        let v = order[head];
+        head += 1;
+        for i in 0..g.degree(v) {
+            let to = g.neighbor(v, i);
+            indeg[to] -= 1;
+            if indeg[to] == 0 {
+                order.push(to);
+            }
+        }
+    }
+
+    if order.len() == n {
+        return Ok(order);
+    }
+
+    // Every remaining vertex has an incoming edge from a remaining vertex.
+    let mut pred = vec![!0; n];
+    for v in (0..n).filter(|&v| indeg[v] > 0) {
+        for i in 0..g.degree(v) {
+            let to = g.neighbor(v, i);
+            if indeg[to] > 0 {
+                pred[to] = v;
+            }
+        }
+    }
+    let mut visited = vec![false; n];
+    let mut v = (0..n).find(|&v| indeg[v] > 0).unwrap();
+    while !visited[v] {
+        visited[v] = true;
+        v = pred[v];
+    }
+    let mut cycle = vec![v];
+    let mut u = pred[v];
+    while u != v {
+        cycle.push(u);
+        u = pred[u];
+    }
+    cycle.reverse();
+    Err(cycle)
+}
+
+#[snippet("lexicographic_topological_sort")]
+#[snippet(include = "Adjacency")]
+#[allow(dead_code)]
+/// Lexicographically smallest topological order. None if the graph is not a DAG.
+pub fn lexicographic_topological_sort<G: Adjacency + ?Sized>(g: &G) -> Option<Vec<usize>> {
+    let n = g.vertices();
+    let mut indeg = vec![0; n];
+    for v in 0..n {
+        for i in 0..g.degree(v) {
+            indeg[g.neighbor(v, i)] += 1;
+        }
+    }
+
+    let mut heap = (0..n)
+        .filter(|&v| indeg[v] == 0)
+        .map(Reverse)
+        .collect::<BinaryHeap<_>>();
+    let mut order = Vec::with_capacity(n);
+    while let Some(Reverse(v)) = heap.pop() {
+        order.push(v);
+        for i in 0..g.degree(v) {
+            let to = g.neighbor(v, i);
+            indeg[to] -= 1;
+            if indeg[to] == 0 {
+                heap.push(Reverse(to));
+            }
+        }
+    }
+
+    if order.len() == n {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+#[snippet("dag_longest_path")]
+#[snippet(include = "topological_sort")]
+#[snippet(include = "Cost")]
+#[snippet(include = "Graph")]
+#[allow(dead_code)]
+/// Longest path in a DAG. Returns (length, vertices). None if the graph is not a DAG.
+pub fn dag_longest_path<C: Cost>(g: &Graph<C>) -> Option<(C, Vec<usize>)> {
+    let order = topological_sort(g).ok()?;
+    let n = g.len();
+    let mut dp = vec![C::zero(); n];
+    let mut prev = vec![None; n];
+    for &v in &order {
+        for &(to, id) in g.adj(v) {
+            let d = dp[v].add(*g.weight(id));
+            if dp[to] < d {
+                dp[to] = d;
+                prev[to] = Some(v);
+            }
+        }
+    }
+
+    let mut v = (0..n).max_by_key(|&v| dp[v])?;
+    let len = dp[v];
+    let mut path = vec![v];
+    while let Some(p) = prev[v] {
+        path.push(p);
+        v = p;
+    }
+    path.reverse();
+    Some((len, path))
+}
+
+#[snippet("dag_count_paths")]
+#[snippet(include = "topological_sort")]
+#[allow(dead_code)]
+/// Number of paths from `s` to each vertex modulo `p`. None if the graph is not a DAG.
+pub fn dag_count_paths<G: Adjacency + ?Sized>(g: &G, s: usize, p: u64) -> Option<Vec<u64>> {
+    let order = topological_sort(g).ok()?;
+    let mut count = vec![0; g.vertices()];
+    count[s] = 1 % p;
+    for &v in &order {
+        for i in 0..g.degree(v) {
+            let to = g.neighbor(v, i);
+            count[to] = (count[to] + count[v]) % p;
+        }
+    }
+    Some(count)
+}
+
+#[snippet("transitive_reduction")]
+#[snippet(include = "topological_sort")]
+#[snippet(include = "BitSet")]
+#[allow(dead_code)]
+/// Transitive reduction of a DAG. O(VE / 64)
+/// Returns None if the graph is not a DAG.
+pub fn transitive_reduction<G: Adjacency + ?Sized>(g: &G) -> Option<Vec<Vec<usize>>> {
+    let order = topological_sort(g).ok()?;
+    let n = g.vertices();
+    let mut pos = vec![0; n];
+    for (i, &v) in order.iter().enumerate() {
+        pos[v] = i;
+    }
+
+    let mut reach = vec![BitSet::new(n); n];
+    let mut res = vec![Vec::new(); n];
+    for &v in order.iter().rev() {
+        let mut next = (0..g.degree(v))
+            .map(|i| g.neighbor(v, i))
+            .collect::<Vec<_>>();
+        next.sort_by_key(|&to| pos[to]);
+        next.dedup();
+
+        let mut r = BitSet::new(n);
+        for to in next {
+            if !r[to] {
+                res[v].push(to);
+                r |= &reach[to];
+            }
+        }
+        r.set(v, true);
+        reach[v] = r;
+    }
+
+    Some(res)
+}
+
+#[cfg(test)]
+fn random_dag<R: rand::Rng>(rng: &mut R, n: usize, m: usize) -> (Vec<usize>, Vec<Vec<usize>>) {
+    let mut perm = (0..n).collect::<Vec<_>>();
+    rng.shuffle(&mut perm);
+    let mut g = vec![Vec::new(); n];
+    for _ in 0..m {
+        let a = rng.next_u32() as usize % n;
+        let b = rng.next_u32() as usize % n;
+        if a < b {
+            g[perm[a]].push(perm[b]);
+        }
+    }
+    (perm, g)
+}
+
+#[test]
+fn test_topological_sort() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..100 {
+        let n = 20;
+        let (perm, mut g) = random_dag(&mut rng, n, 40);
+
+        let order = topological_sort(&g).unwrap();
+        let lex = lexicographic_topological_sort(&g).unwrap();
+        for o in &[&order, &lex] {
+            let mut pos = vec![0; n];
+            for (i, &v) in o.iter().enumerate() {
+                pos[v] = i;
+            }
+            for v in 0..n {
+                for &to in &g[v] {
+                    assert!(pos[v] < pos[to]);
+                }
+            }
+        }
+
+        // Greedy choice of the smallest available vertex
+        let mut used = vec![false; n];
+        for &v in &lex {
+            let smallest = (0..n)
+                .find(|&u| !used[u] && (0..n).all(|w| used[w] || !g[w].contains(&u)))
+                .unwrap();
+            assert_eq!(v, smallest);
+            used[v] = true;
+        }
+
+        // Close a cycle
+        let a = rng.next_u32() as usize % (n - 1);
+        let b = a + 1 + rng.next_u32() as usize % (n - a - 1);
+        g[perm[a]].push(perm[b]);
+        g[perm[b]].push(perm[a]);
+        let cycle = topological_sort(&g).unwrap_err();
+        assert!(lexicographic_topological_sort(&g).is_none());
+        for i in 0..cycle.len() {
+            assert!(g[cycle[i]].contains(&cycle[(i + 1) % cycle.len()]));
+        }
+    }
+}
+
+#[test]
+fn test_dag_helpers() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..100 {
+        let n = 12;
+        let (_, g) = random_dag(&mut rng, n, 30);
+
+        // Brute force with DFS over all paths
+        let mut paths = vec![vec![0u64; n]; n];
+        let mut longest = 0;
+        for (s, ps) in paths.iter_mut().enumerate() {
+            let mut stack = vec![(s, 0)];
+            while let Some((v, len)) = stack.pop() {
+                ps[v] += 1;
+                longest = longest.max(len);
+                for &to in &g[v] {
+                    stack.push((to, len + 1));
+                }
+            }
+        }
+
+        let edges = (0..n)
+            .flat_map(|v| g[v].iter().map(move |&to| (v, to, 1u64)))
+            .collect::<Vec<_>>();
+        let (len, path) = dag_longest_path(&Graph::directed(n, &edges)).unwrap();
+        assert_eq!(len, longest);
+        assert_eq!(path.len() as u64, len + 1);
+        for w in path.windows(2) {
+            assert!(g[w[0]].contains(&w[1]));
+        }
+
+        for (s, p) in paths.iter().enumerate() {
+            assert_eq!(&dag_count_paths(&g, s, 1_000_000_007).unwrap(), p);
+        }
+
+        let reduced = transitive_reduction(&g).unwrap();
+        let reach = |g: &Vec<Vec<usize>>| {
+            (0..n)
+                .map(|s| {
+                    let mut r = vec![false; n];
+                    let mut stack = vec![s];
+                    while let Some(v) = stack.pop() {
+                        for &to in &g[v] {
+                            if !r[to] {
+                                r[to] = true;
+                                stack.push(to);
+                            }
+                        }
+                    }
+                    r
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(reach(&g), reach(&reduced));
+        // Removing any edge changes reachability
+        for v in 0..n {
+            for i in 0..reduced[v].len() {
+                let mut h = reduced.clone();
+                h[v].remove(i);
+                assert_ne!(reach(&h), reach(&reduced));
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::graph::strongly_connected_component;
+use cargo_snippet::snippet;
+
+#[snippet("TwoSat")]
+#[snippet(include = "strongly_connected_component")]
+#[allow(dead_code)]
+/// 2-SAT solver.
+/// A literal `(i, f)` means "variable i is f".
+pub struct TwoSat {
+    n: usize,
+    /// Implication graph. Vertex 2 * i + f is the literal (i, f)
+    g: Vec<Vec<usize>>,
+}
+
+#[snippet("TwoSat")]
+impl TwoSat {
+    #[allow(dead_code)]
+    pub fn new(n: usize) -> TwoSat {
+        TwoSat {
+            n,
+            g: vec![Vec::new(); 2 * n],
+        }
+    }
+
+    #[allow(dead_code)]
+    fn lit(i: usize, f: bool) -> usize {
+        2 * i + f as usize
+    }
+
+    #[allow(dead_code)]
+    /// Add a new variable and return its index
+    pub fn add_var(&mut self) -> usize {
+        self.g.push(Vec::new());
+        self.g.push(Vec::new());
+        self.n += 1;
+        self.n - 1
+    }
+
+    #[allow(dead_code)]
+    /// (x_i == f) or (x_j == g)
+    pub fn add_clause(&mut self, i: usize, f: bool, j: usize, g: bool) {
+        self.g[TwoSat::lit(i, !f)].push(TwoSat::lit(j, g));
+        self.g[TwoSat::lit(j, !g)].push(TwoSat::lit(i, f));
+    }
+
+    #[allow(dead_code)]
+    /// (x_i == f) implies (x_j == g)
+    pub fn add_implication(&mut self, i: usize, f: bool, j: usize, g: bool) {
+        self.add_clause(i, !f, j, g);
+    }
+
+    #[allow(dead_code)]
+    /// x_i == f
+    pub fn set(&mut self, i: usize, f: bool) {
+        self.add_clause(i, f, i, f);
+    }
+
+    #[allow(dead_code)]
+    /// x_i xor x_j == b
+    pub fn add_xor(&mut self, i: usize, j: usize, b: bool) {
+        self.add_clause(i, true, j, b);
+        self.add_clause(i, false, j, !b);
+    }
+
+    #[allow(dead_code)]
+    /// At most one of the literals holds.
+    /// Uses the prefix variable encoding, which adds `lits.len()` auxiliary variables
+    /// after the existing ones. `solve` also returns their values.
+    pub fn at_most_one(&mut self, lits: &[(usize, bool)]) {
+        let mut prev: Option<usize> = None;
+        for &(i, f) in lits {
+            // p <=> some literal so far holds
+            let p = self.add_var();
+            self.add_implication(i, f, p, true);
+            if let Some(q) = prev {
+                self.add_implication(q, true, p, true);
+                self.add_implication(q, true, i, !f);
+            }
+            prev = Some(p);
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Value of every variable, including auxiliary ones added by `at_most_one`.
+    /// Only indices of variables created by `new` or `add_var` are meaningful.
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let cmp = strongly_connected_component(&self.g);
+        (0..self.n)
+            .map(|i| {
+                let t = cmp[TwoSat::lit(i, true)];
+                let f = cmp[TwoSat::lit(i, false)];
+                if t == f {
+                    None
+                } else {
+                    Some(t > f)
+                }
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_two_sat_vs_brute_force() {
+    use rand::{Rng, SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[1, 2, 3, 4, 5]);
+
+    for _ in 0..500 {
+        let n = rng.next_u32() as usize % 8 + 1;
+        let m = rng.next_u32() as usize % 12;
+        let mut clauses = Vec::new();
+        let mut ts = TwoSat::new(n);
+        for _ in 0..m {
+            let i = rng.next_u32() as usize % n;
+            let j = rng.next_u32() as usize % n;
+            let f = rng.gen();
+            let g = rng.gen();
+            match rng.next_u32() % 3 {
+                0 => {
+                    ts.add_clause(i, f, j, g);
+                    clauses.push((i, f, j, g));
+                }
+                1 => {
+                    ts.add_implication(i, f, j, g);
+                    clauses.push((i, !f, j, g));
+                }
+                _ => {
+                    ts.add_xor(i, j, f);
+                    clauses.push((i, true, j, f));
+                    clauses.push((i, false, j, !f));
+                }
+            }
+        }
+        let amo = (0..rng.next_u32() as usize % 4)
+            .map(|_| (rng.next_u32() as usize % n, rng.gen()))
+            .collect::<Vec<(usize, bool)>>();
+        ts.at_most_one(&amo);
+
+        let check = |x: &[bool]| {
+            clauses.iter().all(|&(i, f, j, g)| x[i] == f || x[j] == g)
+                && amo.iter().filter(|&&(i, f)| x[i] == f).count() <= 1
+        };
+        let brute = (0..1 << n)
+            .map(|s: usize| (0..n).map(|i| s >> i & 1 == 1).collect::<Vec<_>>())
+            .any(|x| check(&x));
+
+        let res = ts.solve();
+        assert_eq!(res.is_some(), brute);
+        if let Some(x) = res {
+            assert_eq!(x.len(), n + amo.len());
+            assert!(check(&x[..n]));
+        }
+    }
+}